@@ -7,6 +7,7 @@ use cosmwasm_std::{
     DepsMut,
     Env,
     MessageInfo,
+    Order,
     Response,
     StdError,
     StdResult,
@@ -15,9 +16,15 @@ use cosmwasm_std::{
 };
 use cw20::{ AllowanceResponse, Cw20ReceiveMsg, Expiration };
 
+use crate::enumerable::{ DEFAULT_LIMIT, MAX_LIMIT };
 use crate::error::ContractError;
-use crate::state::{ ALLOWANCES, ALLOWANCES_SPENDER, BALANCES, TOKEN_INFO };
-use crate::contract::capture_total_supply_history;
+use crate::state::{ allowances, ALLOWANCE_PRUNE_CURSORS, SUPPLY_HISTORY, TOKEN_INFO };
+use cw_storage_plus::Bound;
+use crate::contract::{ debit_balance, transfer_tokens };
+use crate::msg::PausableAction;
+use crate::compliance::assert_not_frozen;
+use crate::launch::assert_transfer_allowed;
+use crate::pause::assert_not_paused;
 
 pub fn execute_increase_allowance(
     deps: DepsMut,
@@ -40,11 +47,15 @@ pub fn execute_increase_allowance(
             }
             val.expires = exp;
         }
-        val.allowance += amount;
+        // only asking for exactly the unlimited amount makes the allowance unlimited
+        val.allowance = if amount == UNLIMITED_ALLOWANCE {
+            amount
+        } else {
+            val.allowance.checked_add(amount).map_err(StdError::overflow)?
+        };
         Ok(val)
     };
-    ALLOWANCES.update(deps.storage, (&info.sender, &spender_addr), update_fn)?;
-    ALLOWANCES_SPENDER.update(deps.storage, (&spender_addr, &info.sender), update_fn)?;
+    allowances().update(deps.storage, (&info.sender, &spender_addr), env.block.height, update_fn)?;
 
     let res = Response::new().add_attributes(
         vec![
@@ -72,12 +83,8 @@ pub fn execute_decrease_allowance(
 
     let key = (&info.sender, &spender_addr);
 
-    fn reverse<'a>(t: (&'a Addr, &'a Addr)) -> (&'a Addr, &'a Addr) {
-        (t.1, t.0)
-    }
-
     // load value and delete if it hits 0, or update otherwise
-    let mut allowance = ALLOWANCES.load(deps.storage, key)?;
+    let mut allowance = allowances().load(deps.storage, key)?;
     if amount < allowance.allowance {
         // update the new amount
         allowance.allowance = allowance.allowance.checked_sub(amount).map_err(StdError::overflow)?;
@@ -87,11 +94,9 @@ pub fn execute_decrease_allowance(
             }
             allowance.expires = exp;
         }
-        ALLOWANCES.save(deps.storage, key, &allowance)?;
-        ALLOWANCES_SPENDER.save(deps.storage, reverse(key), &allowance)?;
+        allowances().save(deps.storage, key, &allowance, env.block.height)?;
     } else {
-        ALLOWANCES.remove(deps.storage, key);
-        ALLOWANCES_SPENDER.remove(deps.storage, reverse(key));
+        allowances().remove(deps.storage, key, env.block.height)?;
     }
 
     let res = Response::new().add_attributes(
@@ -105,6 +110,170 @@ pub fn execute_decrease_allowance(
     Ok(res)
 }
 
+/// Replaces the allowance of `spender` on `owner`'s account, deleting it if `amount` is zero.
+pub fn set_allowance(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    owner: &Addr,
+    spender: &Addr,
+    amount: Uint128,
+    expires: Option<Expiration>
+) -> Result<(), ContractError> {
+    if spender == owner {
+        return Err(ContractError::CannotSetOwnAccount {});
+    }
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(block) {
+        return Err(ContractError::InvalidExpiration {});
+    }
+
+    let key = (owner, spender);
+    if amount.is_zero() {
+        allowances().remove(storage, key, block.height)?;
+    } else {
+        let allowance = AllowanceResponse { allowance: amount, expires };
+        allowances().save(storage, key, &allowance, block.height)?;
+    }
+    Ok(())
+}
+
+pub fn execute_set_allowance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    amount: Uint128,
+    expires: Option<Expiration>,
+    expected_current: Option<Uint128>
+) -> Result<Response, ContractError> {
+    let spender_addr = deps.api.addr_validate(&spender)?;
+
+    let current = allowances()
+        .may_load(deps.storage, (&info.sender, &spender_addr))?
+        .unwrap_or_default()
+        .allowance;
+    if let Some(expected) = expected_current {
+        if expected != current {
+            return Err(ContractError::AllowanceMismatch { current });
+        }
+    }
+    set_allowance(deps.storage, &env.block, &info.sender, &spender_addr, amount, expires)?;
+
+    let res = Response::new().add_attributes(
+        vec![
+            attr("action", "set_allowance"),
+            attr("owner", info.sender),
+            attr("spender", spender),
+            attr("amount", amount),
+            attr("previous_amount", current)
+        ]
+    );
+    Ok(res)
+}
+
+pub fn execute_revoke_all_allowances(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    start_after: Option<String>,
+    limit: Option<u32>
+) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into_bytes()));
+
+    let spenders = allowances()
+        .prefix(&info.sender)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    for spender in &spenders {
+        allowances().remove(deps.storage, (&info.sender, spender), env.block.height)?;
+    }
+
+    let res = Response::new().add_attributes(
+        vec![
+            attr("action", "revoke_all_allowances"),
+            attr("owner", info.sender),
+            attr("count", spenders.len().to_string())
+        ]
+    );
+    Ok(res)
+}
+
+pub fn execute_revoke_allowances(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spenders: Vec<String>
+) -> Result<Response, ContractError> {
+    let mut count = 0;
+    for spender in &spenders {
+        let spender_addr = deps.api.addr_validate(spender)?;
+        let key = (&info.sender, &spender_addr);
+        if allowances().may_load(deps.storage, key)?.is_some() {
+            allowances().remove(deps.storage, key, env.block.height)?;
+            count += 1;
+        }
+    }
+
+    let res = Response::new().add_attributes(
+        vec![
+            attr("action", "revoke_allowances"),
+            attr("owner", info.sender),
+            attr("count", count.to_string())
+        ]
+    );
+    Ok(res)
+}
+
+/// Removes up to `limit` expired allowances of `owner`. Failed spends cannot do this
+/// themselves, because their state changes are reverted along with the error.
+pub fn execute_prune_expired_allowances(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    owner: String,
+    limit: Option<u32>
+) -> Result<Response, ContractError> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let cursor = ALLOWANCE_PRUNE_CURSORS.may_load(deps.storage, &owner_addr)?;
+    let start = cursor.as_ref().map(Bound::exclusive);
+    let entries = allowances()
+        .prefix(&owner_addr)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut pruned = 0u32;
+    for (spender, allowance) in &entries {
+        if allowance.expires.is_expired(&env.block) {
+            allowances().remove(deps.storage, (&owner_addr, spender), env.block.height)?;
+            pruned += 1;
+        }
+    }
+    // a short page means all of the owner's allowances were scanned, so the next pass starts over
+    let done = entries.len() < limit;
+    match entries.last() {
+        Some((spender, _)) if !done => ALLOWANCE_PRUNE_CURSORS.save(deps.storage, &owner_addr, spender)?,
+        _ => ALLOWANCE_PRUNE_CURSORS.remove(deps.storage, &owner_addr),
+    }
+
+    let res = Response::new().add_attributes(
+        vec![
+            attr("action", "prune_expired_allowances"),
+            attr("owner", owner),
+            attr("count", pruned.to_string()),
+            attr("done", done.to_string())
+        ]
+    );
+    Ok(res)
+}
+
+/// An allowance of this amount is unlimited: spending from it leaves it untouched.
+pub const UNLIMITED_ALLOWANCE: Uint128 = Uint128::MAX;
+
 // this can be used to update a lower allowance - call bucket.update with proper keys
 pub fn deduct_allowance(
     storage: &mut dyn Storage,
@@ -113,22 +282,21 @@ pub fn deduct_allowance(
     block: &BlockInfo,
     amount: Uint128
 ) -> Result<AllowanceResponse, ContractError> {
-    let update_fn = |current: Option<AllowanceResponse>| -> _ {
-        match current {
-            Some(mut a) => {
-                if a.expires.is_expired(block) {
-                    Err(ContractError::Expired {})
-                } else {
-                    // deduct the allowance if enough
-                    a.allowance = a.allowance.checked_sub(amount).map_err(StdError::overflow)?;
-                    Ok(a)
-                }
-            }
-            None => Err(ContractError::NoAllowance {}),
-        }
-    };
-    ALLOWANCES.update(storage, (owner, spender), update_fn)?;
-    ALLOWANCES_SPENDER.update(storage, (spender, owner), update_fn)
+    let mut allowance = allowances()
+        .may_load(storage, (owner, spender))?
+        .ok_or(ContractError::NoAllowance {})?;
+    if allowance.expires.is_expired(block) {
+        return Err(ContractError::Expired {});
+    }
+    // unlimited allowances are not written back, which saves a write per spend
+    if allowance.allowance == UNLIMITED_ALLOWANCE {
+        return Ok(allowance);
+    }
+
+    // deduct the allowance if enough
+    allowance.allowance = allowance.allowance.checked_sub(amount).map_err(StdError::overflow)?;
+    allowances().save(storage, (owner, spender), &allowance, block.height)?;
+    Ok(allowance)
 }
 
 pub fn execute_transfer_from(
@@ -139,26 +307,16 @@ pub fn execute_transfer_from(
     recipient: String,
     amount: Uint128
 ) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, PausableAction::Transfer)?;
     let rcpt_addr = deps.api.addr_validate(&recipient)?;
     let owner_addr = deps.api.addr_validate(&owner)?;
+    assert_not_frozen(deps.storage, &[&info.sender, &owner_addr, &rcpt_addr])?;
+    assert_transfer_allowed(deps.storage, &owner_addr, &rcpt_addr)?;
 
     // deduct allowance before doing anything else have enough allowance
     deduct_allowance(deps.storage, &owner_addr, &info.sender, &env.block, amount)?;
 
-    BALANCES.update(
-        deps.storage,
-        &owner_addr,
-        env.block.height,
-        |balance: Option<Uint128>| -> StdResult<_> {
-            Ok(balance.unwrap_or_default().checked_sub(amount)?)
-        }
-    )?;
-    BALANCES.update(
-        deps.storage,
-        &rcpt_addr,
-        env.block.height,
-        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) }
-    )?;
+    let fee = transfer_tokens(deps.storage, &env, &owner_addr, &rcpt_addr, amount)?;
 
     let res = Response::new().add_attributes(
         vec![
@@ -166,7 +324,8 @@ pub fn execute_transfer_from(
             attr("from", owner),
             attr("to", recipient),
             attr("by", info.sender),
-            attr("amount", amount)
+            attr("amount", amount),
+            attr("fee_amount", fee)
         ]
     );
     Ok(res)
@@ -179,20 +338,15 @@ pub fn execute_burn_from(
     owner: String,
     amount: Uint128
 ) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, PausableAction::Burn)?;
     let owner_addr = deps.api.addr_validate(&owner)?;
+    assert_not_frozen(deps.storage, &[&info.sender, &owner_addr])?;
 
     // deduct allowance before doing anything else have enough allowance
     deduct_allowance(deps.storage, &owner_addr, &info.sender, &env.block, amount)?;
 
     // lower balance
-    BALANCES.update(
-        deps.storage,
-        &owner_addr,
-        env.block.height,
-        |balance: Option<Uint128>| -> StdResult<_> {
-            Ok(balance.unwrap_or_default().checked_sub(amount)?)
-        }
-    )?;
+    debit_balance(deps.storage, &env, &owner_addr, amount)?;
     // reduce total_supply
     let token_info = TOKEN_INFO.update(
         deps.storage,
@@ -202,7 +356,7 @@ pub fn execute_burn_from(
         }
     )?;
 
-    capture_total_supply_history(deps.storage, &env, token_info.total_supply)?;
+    SUPPLY_HISTORY.record_burn(deps.storage, env.block.height, token_info.total_supply, amount)?;
 
     let res = Response::new().add_attributes(
         vec![
@@ -224,40 +378,31 @@ pub fn execute_send_from(
     amount: Uint128,
     msg: Binary
 ) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, PausableAction::Transfer)?;
     let rcpt_addr = deps.api.addr_validate(&contract)?;
     let owner_addr = deps.api.addr_validate(&owner)?;
+    assert_not_frozen(deps.storage, &[&info.sender, &owner_addr, &rcpt_addr])?;
+    assert_transfer_allowed(deps.storage, &owner_addr, &rcpt_addr)?;
 
     // deduct allowance before doing anything else have enough allowance
     deduct_allowance(deps.storage, &owner_addr, &info.sender, &env.block, amount)?;
 
     // move the tokens to the contract
-    BALANCES.update(
-        deps.storage,
-        &owner_addr,
-        env.block.height,
-        |balance: Option<Uint128>| -> StdResult<_> {
-            Ok(balance.unwrap_or_default().checked_sub(amount)?)
-        }
-    )?;
-    BALANCES.update(
-        deps.storage,
-        &rcpt_addr,
-        env.block.height,
-        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) }
-    )?;
+    let fee = transfer_tokens(deps.storage, &env, &owner_addr, &rcpt_addr, amount)?;
 
     let attrs = vec![
         attr("action", "send_from"),
         attr("from", &owner),
         attr("to", &contract),
         attr("by", &info.sender),
-        attr("amount", amount)
+        attr("amount", amount),
+        attr("fee_amount", fee)
     ];
 
     // create a send message
     let msg = (Cw20ReceiveMsg {
         sender: info.sender.into(),
-        amount,
+        amount: amount - fee,
         msg,
     }).into_cosmos_msg(contract)?;
 
@@ -265,13 +410,36 @@ pub fn execute_send_from(
     Ok(res)
 }
 
-pub fn query_allowance(deps: Deps, owner: String, spender: String) -> StdResult<AllowanceResponse> {
+/// Expired allowances stay stored until pruned, but are reported as zero.
+pub fn query_allowance(
+    deps: Deps,
+    env: Env,
+    owner: String,
+    spender: String
+) -> StdResult<AllowanceResponse> {
     let owner_addr = deps.api.addr_validate(&owner)?;
     let spender_addr = deps.api.addr_validate(&spender)?;
-    let allowance = ALLOWANCES.may_load(deps.storage, (
+    let mut allowance = allowances().may_load(deps.storage, (
         &owner_addr,
         &spender_addr,
     ))?.unwrap_or_default();
+    if allowance.expires.is_expired(&env.block) {
+        allowance.allowance = Uint128::zero();
+    }
+    Ok(allowance)
+}
+
+pub fn query_allowance_at(
+    deps: Deps,
+    owner: String,
+    spender: String,
+    height: u64
+) -> StdResult<AllowanceResponse> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    let allowance = allowances()
+        .may_load_at_height(deps.storage, (&owner_addr, &spender_addr), height)?
+        .unwrap_or_default();
     Ok(allowance)
 }
 
@@ -296,7 +464,7 @@ mod tests {
         addr: T,
         amount: Uint128
     ) -> TokenInfoResponse {
-        let instantiate_msg = classic_terraport::token::InstantiateMsg {
+        let instantiate_msg = crate::msg::InstantiateMsg {
             name: "Auto Gen".to_string(),
             symbol: "AUTO".to_string(),
             decimals: 3,
@@ -306,6 +474,7 @@ mod tests {
             }],
             mint: None,
             marketing: None,
+            ..Default::default()
         };
         let info = mock_info("creator", &[]);
         let env = mock_env();
@@ -324,7 +493,7 @@ mod tests {
         do_instantiate(deps.as_mut(), owner.clone(), Uint128::new(12340000));
 
         // no allowance to start
-        let allowance = query_allowance(deps.as_ref(), owner.clone(), spender.clone()).unwrap();
+        let allowance = query_allowance(deps.as_ref(), mock_env(), owner.clone(), spender.clone()).unwrap();
         assert_eq!(allowance, AllowanceResponse::default());
 
         // set allowance with height expiration
@@ -338,7 +507,7 @@ mod tests {
         execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
         // ensure it looks good
-        let allowance = query_allowance(deps.as_ref(), owner.clone(), spender.clone()).unwrap();
+        let allowance = query_allowance(deps.as_ref(), mock_env(), owner.clone(), spender.clone()).unwrap();
         assert_eq!(allowance, AllowanceResponse {
             allowance: allow1,
             expires,
@@ -353,7 +522,7 @@ mod tests {
             expires: None,
         };
         execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-        let allowance = query_allowance(deps.as_ref(), owner.clone(), spender.clone()).unwrap();
+        let allowance = query_allowance(deps.as_ref(), mock_env(), owner.clone(), spender.clone()).unwrap();
         assert_eq!(allowance, AllowanceResponse {
             allowance: allow2,
             expires,
@@ -369,7 +538,7 @@ mod tests {
             expires: Some(new_expire),
         };
         execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
-        let allowance = query_allowance(deps.as_ref(), owner.clone(), spender.clone()).unwrap();
+        let allowance = query_allowance(deps.as_ref(), mock_env(), owner.clone(), spender.clone()).unwrap();
         assert_eq!(allowance, AllowanceResponse {
             allowance: allow3,
             expires: new_expire,
@@ -382,10 +551,198 @@ mod tests {
             expires: None,
         };
         execute(deps.as_mut(), env, info, msg).unwrap();
-        let allowance = query_allowance(deps.as_ref(), owner, spender).unwrap();
+        let allowance = query_allowance(deps.as_ref(), mock_env(), owner, spender).unwrap();
         assert_eq!(allowance, AllowanceResponse::default());
     }
 
+    #[test]
+    fn set_allowance_overwrites_with_guard() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        let owner = String::from("addr0001");
+        let spender = String::from("addr0002");
+        let info = mock_info(owner.as_ref(), &[]);
+        let env = mock_env();
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(12340000));
+
+        let set = |amount: u128, expected_current: Option<u128>| ExecuteMsg::SetAllowance {
+            spender: spender.clone(),
+            amount: Uint128::new(amount),
+            expires: None,
+            expected_current: expected_current.map(Uint128::new),
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), set(500, Some(0))).unwrap();
+        execute(deps.as_mut(), env.clone(), info.clone(), set(200, None)).unwrap();
+        let allowance = query_allowance(deps.as_ref(), mock_env(), owner.clone(), spender.clone()).unwrap();
+        assert_eq!(allowance.allowance, Uint128::new(200));
+
+        // the spender used part of it in the meantime
+        let msg = ExecuteMsg::TransferFrom {
+            owner: owner.clone(),
+            recipient: spender.clone(),
+            amount: Uint128::new(50),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(spender.as_ref(), &[]), msg).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), set(400, Some(200)));
+        assert_eq!(err.unwrap_err(), ContractError::AllowanceMismatch {
+            current: Uint128::new(150),
+        });
+
+        // setting zero deletes the entry, from both the owner and the spender side
+        execute(deps.as_mut(), env.clone(), info.clone(), set(0, Some(150))).unwrap();
+        let key = (&Addr::unchecked(&owner), &Addr::unchecked(&spender));
+        assert_eq!(allowances().may_load(deps.as_ref().storage, key).unwrap(), None);
+        let spender_entries = allowances()
+            .idx.spender.prefix(Addr::unchecked(&spender))
+            .keys(deps.as_ref().storage, None, None, Order::Ascending)
+            .count();
+        assert_eq!(spender_entries, 0);
+
+        let err = execute(deps.as_mut(), env, mock_info(owner.as_ref(), &[]), ExecuteMsg::SetAllowance {
+            spender: owner.clone(),
+            amount: Uint128::new(1),
+            expires: None,
+            expected_current: None,
+        });
+        assert_eq!(err.unwrap_err(), ContractError::CannotSetOwnAccount {});
+    }
+
+    #[test]
+    fn revoke_allowances_in_bulk() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        let owner = String::from("addr0001");
+        let info = mock_info(owner.as_ref(), &[]);
+        let env = mock_env();
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(12340000));
+
+        let spenders = (1..=4).map(|i| format!("spender{}", i)).collect::<Vec<_>>();
+        for spender in &spenders {
+            let msg = ExecuteMsg::IncreaseAllowance {
+                spender: spender.clone(),
+                amount: Uint128::new(100),
+                expires: None,
+            };
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        let msg = ExecuteMsg::RevokeAllowances {
+            spenders: vec!["spender2".into(), "unknown".into()],
+        };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert_eq!(res.attributes[2], attr("count", "1"));
+
+        let msg = ExecuteMsg::RevokeAllAllowances { start_after: None, limit: Some(2) };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+        assert_eq!(res.attributes[2], attr("count", "2"));
+        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert_eq!(res.attributes[2], attr("count", "1"));
+
+        for spender in spenders {
+            let allowance = query_allowance(deps.as_ref(), mock_env(), owner.clone(), spender.clone()).unwrap();
+            assert_eq!(allowance, AllowanceResponse::default());
+            let by_spender = allowances()
+                .idx.spender.prefix(Addr::unchecked(spender))
+                .keys(deps.as_ref().storage, None, None, Order::Ascending)
+                .count();
+            assert_eq!(by_spender, 0);
+        }
+    }
+
+    #[test]
+    fn unlimited_allowance_is_not_spent() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        let owner = String::from("addr0001");
+        let spender = String::from("addr0002");
+        let env = mock_env();
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(12340000));
+
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: spender.clone(),
+            amount: Uint128::new(1000),
+            expires: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(owner.as_ref(), &[]), msg).unwrap();
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: spender.clone(),
+            amount: UNLIMITED_ALLOWANCE,
+            expires: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(owner.as_ref(), &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::TransferFrom {
+            owner: owner.clone(),
+            recipient: spender.clone(),
+            amount: Uint128::new(5000),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(spender.as_ref(), &[]), msg).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), &spender), Uint128::new(5000));
+
+        let allowance = query_allowance(deps.as_ref(), mock_env(), owner.clone(), spender.clone()).unwrap();
+        assert_eq!(allowance.allowance, UNLIMITED_ALLOWANCE);
+
+        // decreasing turns it back into a regular allowance
+        let msg = ExecuteMsg::DecreaseAllowance {
+            spender: spender.clone(),
+            amount: UNLIMITED_ALLOWANCE - Uint128::new(100),
+            expires: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(owner.as_ref(), &[]), msg).unwrap();
+        let msg = ExecuteMsg::TransferFrom {
+            owner: owner.clone(),
+            recipient: spender.clone(),
+            amount: Uint128::new(60),
+        };
+        execute(deps.as_mut(), env, mock_info(spender.as_ref(), &[]), msg).unwrap();
+        let allowance = query_allowance(deps.as_ref(), mock_env(), owner.clone(), spender.clone()).unwrap();
+        assert_eq!(allowance.allowance, Uint128::new(40));
+
+        // a top-up that overflows fails instead of making the allowance unlimited
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: spender.clone(),
+            amount: UNLIMITED_ALLOWANCE - Uint128::new(10),
+            expires: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(owner.as_ref(), &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Std(StdError::Overflow { .. })));
+        let allowance = query_allowance(deps.as_ref(), mock_env(), owner, spender).unwrap();
+        assert_eq!(allowance.allowance, Uint128::new(40));
+    }
+
+    #[test]
+    fn allowance_history_by_height() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        let owner = String::from("addr0001");
+        let spender = String::from("addr0002");
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(12340000));
+
+        let mut env = mock_env();
+        let start = env.block.height;
+        let info = mock_info(owner.as_ref(), &[]);
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: spender.clone(),
+            amount: Uint128::new(500),
+            expires: None,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        env.block.height += 10;
+        let msg = ExecuteMsg::DecreaseAllowance {
+            spender: spender.clone(),
+            amount: Uint128::new(500),
+            expires: None,
+        };
+        execute(deps.as_mut(), env, info, msg).unwrap();
+
+        let at = |height: u64| {
+            query_allowance_at(deps.as_ref(), owner.clone(), spender.clone(), height)
+                .unwrap()
+                .allowance
+        };
+        assert_eq!(at(start), Uint128::zero());
+        assert_eq!(at(start + 1), Uint128::new(500));
+        assert_eq!(at(start + 10), Uint128::new(500));
+        assert_eq!(at(start + 11), Uint128::zero());
+    }
+
     #[test]
     fn allowances_independent() {
         let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
@@ -399,15 +756,15 @@ mod tests {
 
         // no allowance to start
         assert_eq!(
-            query_allowance(deps.as_ref(), owner.clone(), spender.clone()).unwrap(),
+            query_allowance(deps.as_ref(), mock_env(), owner.clone(), spender.clone()).unwrap(),
             AllowanceResponse::default()
         );
         assert_eq!(
-            query_allowance(deps.as_ref(), owner.clone(), spender2.clone()).unwrap(),
+            query_allowance(deps.as_ref(), mock_env(), owner.clone(), spender2.clone()).unwrap(),
             AllowanceResponse::default()
         );
         assert_eq!(
-            query_allowance(deps.as_ref(), spender.clone(), spender2.clone()).unwrap(),
+            query_allowance(deps.as_ref(), mock_env(), spender.clone(), spender2.clone()).unwrap(),
             AllowanceResponse::default()
         );
 
@@ -440,15 +797,15 @@ mod tests {
             expires: Expiration::Never {},
         };
         assert_eq!(
-            query_allowance(deps.as_ref(), owner.clone(), spender.clone()).unwrap(),
+            query_allowance(deps.as_ref(), mock_env(), owner.clone(), spender.clone()).unwrap(),
             expect_one
         );
         assert_eq!(
-            query_allowance(deps.as_ref(), owner.clone(), spender2.clone()).unwrap(),
+            query_allowance(deps.as_ref(), mock_env(), owner.clone(), spender2.clone()).unwrap(),
             expect_two
         );
         assert_eq!(
-            query_allowance(deps.as_ref(), spender.clone(), spender2.clone()).unwrap(),
+            query_allowance(deps.as_ref(), mock_env(), spender.clone(), spender2.clone()).unwrap(),
             AllowanceResponse::default()
         );
 
@@ -468,11 +825,11 @@ mod tests {
             expires: expires3,
         };
         assert_eq!(
-            query_allowance(deps.as_ref(), owner.clone(), spender.clone()).unwrap(),
+            query_allowance(deps.as_ref(), mock_env(), owner.clone(), spender.clone()).unwrap(),
             expect_one
         );
-        assert_eq!(query_allowance(deps.as_ref(), owner, spender2.clone()).unwrap(), expect_two);
-        assert_eq!(query_allowance(deps.as_ref(), spender, spender2).unwrap(), expect_three);
+        assert_eq!(query_allowance(deps.as_ref(), mock_env(), owner, spender2.clone()).unwrap(), expect_two);
+        assert_eq!(query_allowance(deps.as_ref(), mock_env(), spender, spender2).unwrap(), expect_three);
     }
 
     #[test]
@@ -541,7 +898,7 @@ mod tests {
         assert_eq!(get_balance(deps.as_ref(), rcpt.clone()), transfer);
 
         // ensure it looks good
-        let allowance = query_allowance(deps.as_ref(), owner.clone(), spender.clone()).unwrap();
+        let allowance = query_allowance(deps.as_ref(), mock_env(), owner.clone(), spender.clone()).unwrap();
         let expect = AllowanceResponse {
             allowance: allow1.checked_sub(transfer).unwrap(),
             expires: Expiration::Never {},
@@ -573,13 +930,17 @@ mod tests {
 
         // we should now get the expiration error
         let msg = ExecuteMsg::TransferFrom {
-            owner,
+            owner: owner.clone(),
             recipient: rcpt,
             amount: Uint128::new(33443),
         };
         let info = mock_info(spender.as_ref(), &[]);
-        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
         assert_eq!(err, ContractError::Expired {});
+
+        // the expired allowance is still stored, but reported as zero
+        let allowance = query_allowance(deps.as_ref(), env, owner, spender).unwrap();
+        assert_eq!(allowance.allowance, Uint128::zero());
     }
 
     #[test]
@@ -617,7 +978,7 @@ mod tests {
         assert_eq!(get_balance(deps.as_ref(), owner.clone()), start.checked_sub(transfer).unwrap());
 
         // ensure it looks good
-        let allowance = query_allowance(deps.as_ref(), owner.clone(), spender.clone()).unwrap();
+        let allowance = query_allowance(deps.as_ref(), mock_env(), owner.clone(), spender.clone()).unwrap();
         let expect = AllowanceResponse {
             allowance: allow1.checked_sub(transfer).unwrap(),
             expires: Expiration::Never {},
@@ -717,7 +1078,7 @@ mod tests {
         assert_eq!(get_balance(deps.as_ref(), contract.clone()), transfer);
 
         // ensure it looks good
-        let allowance = query_allowance(deps.as_ref(), owner.clone(), spender.clone()).unwrap();
+        let allowance = query_allowance(deps.as_ref(), mock_env(), owner.clone(), spender.clone()).unwrap();
         let expect = AllowanceResponse {
             allowance: allow1.checked_sub(transfer).unwrap(),
             expires: Expiration::Never {},
@@ -811,7 +1172,7 @@ mod tests {
         execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
         // ensure it looks good
-        let allowance = query_allowance(deps.as_ref(), owner.clone(), spender.clone()).unwrap();
+        let allowance = query_allowance(deps.as_ref(), mock_env(), owner.clone(), spender.clone()).unwrap();
         assert_eq!(allowance, AllowanceResponse {
             allowance: allow,
             expires,
@@ -829,7 +1190,7 @@ mod tests {
         execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
         // ensure it looks good
-        let allowance = query_allowance(deps.as_ref(), owner.clone(), spender.clone()).unwrap();
+        let allowance = query_allowance(deps.as_ref(), mock_env(), owner.clone(), spender.clone()).unwrap();
         assert_eq!(allowance, AllowanceResponse {
             allowance: allow + allow, // we increased twice
             expires,
@@ -862,7 +1223,7 @@ mod tests {
         execute(deps.as_mut(), env, info, msg).unwrap();
 
         // ensure it looks good
-        let allowance = query_allowance(deps.as_ref(), owner, spender).unwrap();
+        let allowance = query_allowance(deps.as_ref(), mock_env(), owner, spender).unwrap();
         assert_eq!(allowance, AllowanceResponse {
             allowance: allow,
             expires,
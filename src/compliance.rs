@@ -0,0 +1,196 @@
+use cosmwasm_std::{ Addr, DepsMut, Env, MessageInfo, Response, Storage };
+
+use crate::error::ContractError;
+use crate::roles::{ assert_primary_holder, assert_role };
+use crate::state::{ ComplianceConfig, Role, COMPLIANCE_CONFIG, FROZEN };
+
+/// Fails with `ContractError::AccountFrozen` if any of the given addresses is frozen.
+pub fn assert_not_frozen(storage: &dyn Storage, addresses: &[&Addr]) -> Result<(), ContractError> {
+    match addresses.iter().find(|address| FROZEN.has(storage, address)) {
+        Some(address) => Err(ContractError::AccountFrozen { address: address.to_string() }),
+        None => Ok(()),
+    }
+}
+
+fn load_for_compliance_admin(
+    storage: &dyn Storage,
+    info: &MessageInfo
+) -> Result<ComplianceConfig, ContractError> {
+    assert_role(storage, Role::Compliance, &info.sender)?;
+    Ok(COMPLIANCE_CONFIG.may_load(storage)?.unwrap_or_default())
+}
+
+pub fn execute_freeze(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    address: String,
+    reason: String
+) -> Result<Response, ContractError> {
+    load_for_compliance_admin(deps.storage, &info)?;
+    let addr = deps.api.addr_validate(&address)?;
+    FROZEN.save(deps.storage, &addr, &reason)?;
+
+    let res = Response::new()
+        .add_attribute("action", "freeze")
+        .add_attribute("address", address)
+        .add_attribute("reason", reason);
+    Ok(res)
+}
+
+pub fn execute_unfreeze(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    address: String
+) -> Result<Response, ContractError> {
+    load_for_compliance_admin(deps.storage, &info)?;
+    let addr = deps.api.addr_validate(&address)?;
+    FROZEN.remove(deps.storage, &addr);
+
+    let res = Response::new()
+        .add_attribute("action", "unfreeze")
+        .add_attribute("address", address);
+    Ok(res)
+}
+
+pub fn execute_update_compliance_admin(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    new_admin: Option<String>
+) -> Result<Response, ContractError> {
+    assert_primary_holder(deps.storage, Role::Compliance, &info.sender)?;
+    let mut config = COMPLIANCE_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    config.admin = new_admin.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+    COMPLIANCE_CONFIG.save(deps.storage, &config)?;
+
+    let res = Response::new()
+        .add_attribute("action", "update_compliance_admin")
+        .add_attribute(
+            "new_admin",
+            config.admin.map(Into::into).unwrap_or_else(|| "None".to_string())
+        );
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::{ mock_dependencies, mock_env, mock_info };
+    use cosmwasm_std::{ Binary, Uint128 };
+    use cw20::{ Cw20Coin, MinterResponse };
+
+    use crate::contract::{ execute, instantiate, query_balance };
+    use crate::enumerable::{ query_all_frozen, query_is_frozen };
+    use crate::msg::{ ExecuteMsg, FrozenAccount, InstantiateMsg, IsFrozenResponse };
+
+    fn do_instantiate(deps: DepsMut) {
+        let instantiate_msg = InstantiateMsg {
+            name: "Auto Gen".to_string(),
+            symbol: "AUTO".to_string(),
+            decimals: 3,
+            initial_balances: vec![Cw20Coin {
+                address: "owner".into(),
+                amount: Uint128::new(1000),
+            }],
+            mint: Some(MinterResponse {
+                minter: "minter".into(),
+                cap: None,
+            }),
+            compliance_admin: Some("compliance".into()),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[]);
+        instantiate(deps, mock_env(), info, instantiate_msg).unwrap();
+    }
+
+    fn freeze(address: &str) -> ExecuteMsg {
+        ExecuteMsg::Freeze { address: address.into(), reason: "sanctioned".into() }
+    }
+
+    #[test]
+    fn only_compliance_admin_can_freeze() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), freeze("owner"));
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+
+        let admin = mock_info("compliance", &[]);
+        execute(deps.as_mut(), mock_env(), admin.clone(), freeze("bob")).unwrap();
+        execute(deps.as_mut(), mock_env(), admin.clone(), freeze("alice")).unwrap();
+        let res = query_is_frozen(deps.as_ref(), "bob".into()).unwrap();
+        assert_eq!(res, IsFrozenResponse { frozen: true, reason: Some("sanctioned".into()) });
+
+        let msg = ExecuteMsg::Unfreeze { address: "bob".into() };
+        execute(deps.as_mut(), mock_env(), admin, msg).unwrap();
+        assert!(!query_is_frozen(deps.as_ref(), "bob".into()).unwrap().frozen);
+        let res = query_all_frozen(deps.as_ref(), None, None).unwrap();
+        assert_eq!(res.accounts, vec![FrozenAccount {
+            address: "alice".into(),
+            reason: "sanctioned".into(),
+        }]);
+    }
+
+    #[test]
+    fn frozen_accounts_cannot_move_tokens() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+        let owner = mock_info("owner", &[]);
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: "spender".into(),
+            amount: Uint128::new(500),
+            expires: None,
+        };
+        execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+
+        let admin = mock_info("compliance", &[]);
+        execute(deps.as_mut(), mock_env(), admin.clone(), freeze("bob")).unwrap();
+        let frozen = ContractError::AccountFrozen { address: "bob".into() };
+
+        // nothing can be sent to a frozen account, directly, through an allowance or by minting
+        let msg = ExecuteMsg::Transfer { recipient: "bob".into(), amount: Uint128::new(1) };
+        let err = execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap_err();
+        assert_eq!(err, frozen);
+        let msg = ExecuteMsg::TransferFrom {
+            owner: "owner".into(),
+            recipient: "bob".into(),
+            amount: Uint128::new(1),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("spender", &[]), msg).unwrap_err();
+        assert_eq!(err, frozen);
+        let msg = ExecuteMsg::Mint { recipient: "bob".into(), amount: Uint128::new(1) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg).unwrap_err();
+        assert_eq!(err, frozen);
+
+        // a frozen owner's allowances cannot be spent, and a frozen spender cannot spend
+        execute(deps.as_mut(), mock_env(), admin.clone(), freeze("owner")).unwrap();
+        let msg = ExecuteMsg::SendFrom {
+            owner: "owner".into(),
+            contract: "contract".into(),
+            amount: Uint128::new(1),
+            msg: Binary::default(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("spender", &[]), msg.clone());
+        assert_eq!(err.unwrap_err(), ContractError::AccountFrozen { address: "owner".into() });
+        let msg = ExecuteMsg::Burn { amount: Uint128::new(1) };
+        let err = execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::AccountFrozen { address: "owner".into() });
+
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::Unfreeze {
+            address: "owner".into(),
+        }).unwrap();
+        execute(deps.as_mut(), mock_env(), admin, freeze("spender")).unwrap();
+        let msg = ExecuteMsg::BurnFrom { owner: "owner".into(), amount: Uint128::new(1) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("spender", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::AccountFrozen { address: "spender".into() });
+
+        let msg = ExecuteMsg::BatchTransfer {
+            transfers: vec![Cw20Coin { address: "carol".into(), amount: Uint128::new(10) }],
+        };
+        execute(deps.as_mut(), mock_env(), owner, msg).unwrap();
+        assert_eq!(query_balance(deps.as_ref(), "carol".into()).unwrap().balance, Uint128::new(10));
+    }
+}
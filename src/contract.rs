@@ -1,21 +1,21 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::{ to_binary, entry_point };
 use cosmwasm_std::{
+    Addr,
     Storage,
-    Order,
     Uint128,
     Binary,
     Deps,
     DepsMut,
+    Empty,
     Env,
     MessageInfo,
     Response,
     StdError,
     StdResult,
 };
-use cw_storage_plus::Bound;
 
-use cw2::set_contract_version;
+use cw2::{ get_contract_version, set_contract_version };
 use cw20::{
     BalanceResponse,
     Cw20Coin,
@@ -29,30 +29,202 @@ use cw20::{
     TokenInfoResponse,
 };
 use crate::error::ContractError;
-use crate::msg::{ ExecuteMsg, QueryMsg };
+use crate::msg::{
+    BatchSendItem,
+    ExecuteMsg,
+    HolderCountResponse,
+    InstantiateMsg,
+    MigrateMsg,
+    PausableAction,
+    QueryMsg,
+};
 
 use crate::allowances::{
     execute_burn_from,
     execute_decrease_allowance,
     execute_increase_allowance,
+    execute_prune_expired_allowances,
+    execute_revoke_all_allowances,
+    execute_revoke_allowances,
     execute_send_from,
+    execute_set_allowance,
     execute_transfer_from,
     query_allowance,
+    query_allowance_at,
+};
+
+use crate::permit::{ execute_permit, query_nonce };
+
+use crate::enumerable::{
+    query_all_accounts,
+    query_all_frozen,
+    query_all_minters,
+    query_mint_proposals,
+    query_fee_exemptions,
+    query_is_frozen,
+    query_limit_exemptions,
+    query_owner_allowances,
+    query_pending_operations,
+    query_role_members,
+    query_spender_allowances,
+    query_supply_history,
+    query_transfer_allowlist,
+};
+
+use crate::fees::{
+    execute_update_fee_exemptions,
+    execute_update_transfer_fee,
+    query_transfer_fee,
+    transfer_fee,
+    validate_transfer_fee,
+};
+
+use crate::minters::{
+    consume_minter_allowance,
+    execute_accept_minter,
+    execute_cancel_minter_proposal,
+    execute_propose_minter,
+    execute_remove_minter,
+    execute_set_minter,
+    execute_update_mint_cap,
+    query_pending_minter,
+};
+
+use crate::mint_proposals::{
+    execute_approve_mint,
+    execute_propose_mint,
+    execute_update_mint_approval,
+    query_mint_approval,
+    query_mint_proposal,
+    validate_mint_approval,
+};
+
+use crate::vesting::{
+    create_vesting_accounts,
+    execute_mint_vesting,
+    locked_amount,
+    query_spendable_balance,
+    query_vesting_schedule,
+};
+
+use crate::votes::{
+    execute_delegate,
+    query_delegation,
+    query_total_voting_power_at,
+    query_voting_power,
+    query_voting_power_at,
+    update_voting_power,
+};
+
+use crate::ownership::{
+    execute_accept_ownership,
+    execute_renounce_ownership,
+    execute_transfer_ownership,
+    query_ownership,
 };
 
-use crate::enumerable::{ query_all_accounts, query_owner_allowances, query_spender_allowances };
+use crate::roles::{
+    assert_primary_holder,
+    assert_role,
+    execute_grant_role,
+    execute_renounce_role,
+    execute_revoke_role,
+    execute_set_role_admin,
+    has_role,
+    query_has_role,
+};
+
+use crate::timelock::{
+    execute_cancel_operation,
+    execute_execute_operation,
+    execute_schedule_operation,
+    execute_update_timelock_delay,
+    query_timelock,
+};
+
+use crate::pause::{
+    assert_not_paused,
+    execute_pause,
+    execute_unpause,
+    execute_update_pause_guardian,
+    query_pause_info,
+};
+
+use crate::compliance::{
+    assert_not_frozen,
+    execute_freeze,
+    execute_unfreeze,
+    execute_update_compliance_admin,
+};
+
+use crate::launch::{
+    assert_transfer_allowed,
+    execute_open_trading,
+    execute_update_launch_limits,
+    execute_update_limit_exemptions,
+    execute_update_transfer_allowlist,
+    limits_for,
+    query_launch_limits,
+    query_transfer_restriction,
+};
+
+use crate::history::{
+    balance_at,
+    balances,
+    execute_checkpoint,
+    execute_prune_balance_history,
+    execute_prune_supply_history,
+    query_balance_history,
+};
+
+use crate::migrations::{
+    execute_continue_migration,
+    holder_count_pending,
+    holder_counted,
+    run_pending_migrations,
+    schedule_migration,
+    DEFAULT_MIGRATION_LIMIT,
+};
 
 use crate::state::{
     BALANCES,
     MARKETING_INFO,
     LOGO,
     TOKEN_INFO,
-    TOTAL_SUPPLY_HISTORY,
+    SUPPLY_HISTORY,
+    LEGACY_TOTAL_SUPPLY_HISTORY,
+    HOLDER_COUNT,
+    HISTORY_CONFIG,
+    FEE_CONFIG,
+    FEE_EXEMPT,
+    OWNER,
+    PAUSE_STATE,
+    COMPLIANCE_CONFIG,
+    TRANSFER_RESTRICTION,
+    TRANSFER_ALLOWLIST,
+    LAUNCH_LIMITS,
+    LIMIT_EXEMPT,
+    MINTERS,
+    MINT_APPROVAL_CONFIG,
+    MINT_CAP_CONFIG,
+    PENDING_MINTER,
+    ROLES,
+    TIMELOCK_DELAY,
+    ComplianceConfig,
+    FeeConfig,
+    HistoryConfig,
+    LaunchLimits,
+    MigrationStep,
+    MintCapConfig,
     MinterData,
+    Ownership,
+    PauseState,
+    Role,
     TokenInfo,
+    TransferRestriction,
 };
 
-use classic_terraport::token::InstantiateMsg;
+use semver::Version;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:terraport-token";
@@ -128,11 +300,17 @@ pub fn instantiate(
     // check valid token info
     msg.validate()?;
 
+    HISTORY_CONFIG.save(deps.storage, &HistoryConfig {
+        balance_strategy: msg.balance_snapshot_strategy.unwrap_or_default(),
+        retention: msg.history_retention,
+    })?;
+
     // create initial accounts
     let total_supply = create_accounts(&mut deps, &msg.initial_balances, env.block.height)?;
+    create_vesting_accounts(&mut deps, msg.initial_vesting.as_deref().unwrap_or_default())?;
 
     if !total_supply.is_zero() {
-        capture_total_supply_history(deps.storage, &env, total_supply)?;
+        SUPPLY_HISTORY.record_mint(deps.storage, env.block.height, total_supply, total_supply)?;
     }
 
     if let Some(limit) = msg.get_cap() {
@@ -143,6 +321,24 @@ pub fn instantiate(
         }
     }
 
+    if let (Some(cap), Some(ceiling)) = (msg.get_cap(), msg.mint_cap_ceiling) {
+        if cap > ceiling {
+            return Err(ContractError::CapAboveCeiling {});
+        }
+    }
+    MINT_CAP_CONFIG.save(deps.storage, &MintCapConfig {
+        admin: msg.mint_cap_admin.map(|addr| deps.api.addr_validate(&addr)).transpose()?,
+        ceiling: msg.mint_cap_ceiling,
+    })?;
+    for address in msg.mint_signers.unwrap_or_default() {
+        let addr = deps.api.addr_validate(&address)?;
+        ROLES.save(deps.storage, (Role::MintSigner.as_str(), &addr), &Empty {})?;
+    }
+    if let Some(config) = msg.mint_approval {
+        validate_mint_approval(deps.storage, &config)?;
+        MINT_APPROVAL_CONFIG.save(deps.storage, &config)?;
+    }
+
     let mint = match msg.mint {
         Some(m) =>
             Some(MinterData {
@@ -183,6 +379,51 @@ pub fn instantiate(
         };
         MARKETING_INFO.save(deps.storage, &data)?;
     }
+
+    OWNER.save(deps.storage, &Ownership {
+        owner: msg.owner.map(|addr| deps.api.addr_validate(&addr)).transpose()?,
+        ..Ownership::default()
+    })?;
+    if let Some(delay) = msg.timelock_delay {
+        TIMELOCK_DELAY.save(deps.storage, &delay)?;
+    }
+
+    let pause_state = PauseState {
+        guardian: msg.pause_guardian.map(|addr| deps.api.addr_validate(&addr)).transpose()?,
+        ..PauseState::default()
+    };
+    PAUSE_STATE.save(deps.storage, &pause_state)?;
+    COMPLIANCE_CONFIG.save(deps.storage, &ComplianceConfig {
+        admin: msg.compliance_admin.map(|addr| deps.api.addr_validate(&addr)).transpose()?,
+    })?;
+
+    TRANSFER_RESTRICTION.save(deps.storage, &TransferRestriction {
+        admin: msg.launch_admin.map(|addr| deps.api.addr_validate(&addr)).transpose()?,
+        allowlist_only: msg.allowlist_only.unwrap_or_default(),
+    })?;
+    for address in msg.transfer_allowlist.unwrap_or_default() {
+        TRANSFER_ALLOWLIST.save(deps.storage, &deps.api.addr_validate(&address)?, &Empty {})?;
+    }
+    LAUNCH_LIMITS.save(deps.storage, &LaunchLimits {
+        max_tx_amount: msg.max_tx_amount,
+        max_wallet_balance: msg.max_wallet_balance,
+    })?;
+    for address in msg.limit_exemptions.unwrap_or_default() {
+        LIMIT_EXEMPT.save(deps.storage, &deps.api.addr_validate(&address)?, &Empty {})?;
+    }
+
+    let fee_config = match msg.transfer_fee {
+        Some(fee) => validate_transfer_fee(deps.api, fee)?,
+        None => FeeConfig::default(),
+    };
+    FEE_CONFIG.save(deps.storage, &FeeConfig {
+        admin: msg.fee_admin.map(|addr| deps.api.addr_validate(&addr)).transpose()?,
+        ..fee_config
+    })?;
+    for address in msg.fee_exemptions.unwrap_or_default() {
+        FEE_EXEMPT.save(deps.storage, &deps.api.addr_validate(&address)?, &Empty {})?;
+    }
+
     Ok(Response::default())
 }
 
@@ -194,30 +435,44 @@ pub fn create_accounts(
     validate_accounts(accounts)?;
 
     let mut total_supply = Uint128::zero();
+    let mut holders = 0u64;
     for row in accounts {
         let address = deps.api.addr_validate(&row.address)?;
-        BALANCES.save(deps.storage, &address, &row.amount, height)?;
+        balances(deps.storage)?.save(deps.storage, &address, &row.amount, height)?;
         total_supply += row.amount;
+        if !row.amount.is_zero() {
+            holders += 1;
+        }
     }
+    HOLDER_COUNT.save(deps.storage, &holders, height)?;
 
     Ok(total_supply)
 }
 
 pub fn validate_accounts(accounts: &[Cw20Coin]) -> Result<(), ContractError> {
-    let mut addresses = accounts
-        .iter()
-        .map(|c| &c.address)
-        .collect::<Vec<_>>();
-    addresses.sort();
-    addresses.dedup();
-
-    if addresses.len() != accounts.len() {
+    if has_duplicates(accounts.iter().map(|c| &c.address)) {
         Err(ContractError::DuplicateInitialBalanceAddresses {})
     } else {
         Ok(())
     }
 }
 
+fn has_duplicates<'a>(addresses: impl ExactSizeIterator<Item = &'a String>) -> bool {
+    let count = addresses.len();
+    let mut addresses = addresses.collect::<Vec<_>>();
+    addresses.sort();
+    addresses.dedup();
+
+    addresses.len() != count
+}
+
+/// Sums the amounts of a batch, failing on overflow instead of wrapping.
+fn batch_total(mut amounts: impl Iterator<Item = Uint128>) -> StdResult<Uint128> {
+    amounts.try_fold(Uint128::zero(), |total, amount| {
+        total.checked_add(amount).map_err(StdError::overflow)
+    })
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -225,10 +480,18 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg
 ) -> Result<Response, ContractError> {
+    if msg.is_timelocked() && TIMELOCK_DELAY.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::TimelockRequired {});
+    }
+
     match msg {
         ExecuteMsg::Transfer { recipient, amount } => {
             execute_transfer(deps, env, info, recipient, amount)
         }
+        ExecuteMsg::BatchTransfer { transfers } => {
+            execute_batch_transfer(deps, env, info, transfers)
+        }
+        ExecuteMsg::BatchSend { sends } => execute_batch_send(deps, env, info, sends),
         ExecuteMsg::Burn { amount } => execute_burn(deps, env, info, amount),
         ExecuteMsg::Send { contract, amount, msg } =>
             execute_send(deps, env, info, contract, amount, msg),
@@ -248,6 +511,98 @@ pub fn execute(
         ExecuteMsg::UpdateMinter { new_minter } => {
             execute_update_minter(deps, env, info, new_minter)
         }
+        ExecuteMsg::SetAllowance { spender, amount, expires, expected_current } =>
+            execute_set_allowance(deps, env, info, spender, amount, expires, expected_current),
+        ExecuteMsg::RevokeAllAllowances { start_after, limit } =>
+            execute_revoke_all_allowances(deps, env, info, start_after, limit),
+        ExecuteMsg::RevokeAllowances { spenders } =>
+            execute_revoke_allowances(deps, env, info, spenders),
+        ExecuteMsg::PruneExpiredAllowances { owner, limit } =>
+            execute_prune_expired_allowances(deps, env, info, owner, limit),
+        ExecuteMsg::Permit { owner_pubkey, spender, amount, expires, nonce, signature } =>
+            execute_permit(
+                deps,
+                env,
+                info,
+                owner_pubkey,
+                spender,
+                amount,
+                expires,
+                nonce,
+                signature
+            ),
+        ExecuteMsg::Delegate { delegatee } => execute_delegate(deps, env, info, delegatee),
+        ExecuteMsg::MintVesting { recipient, schedule } =>
+            execute_mint_vesting(deps, env, info, recipient, schedule),
+        ExecuteMsg::UpdateMintCap { cap } => execute_update_mint_cap(deps, env, info, cap),
+        ExecuteMsg::ProposeMinter { new_minter, expires } =>
+            execute_propose_minter(deps, env, info, new_minter, expires),
+        ExecuteMsg::AcceptMinter {} => execute_accept_minter(deps, env, info),
+        ExecuteMsg::CancelMinterProposal {} => execute_cancel_minter_proposal(deps, env, info),
+        ExecuteMsg::SetMinter { minter, cap, rate_limit, expires } =>
+            execute_set_minter(deps, env, info, minter, cap, rate_limit, expires),
+        ExecuteMsg::RemoveMinter { minter } => execute_remove_minter(deps, env, info, minter),
+        ExecuteMsg::ProposeMint { recipient, amount, memo } => {
+            execute_propose_mint(deps, env, info, recipient, amount, memo)
+        }
+        ExecuteMsg::ApproveMint { id } => execute_approve_mint(deps, env, info, id),
+        ExecuteMsg::UpdateMintApproval { config } => {
+            execute_update_mint_approval(deps, env, info, config)
+        }
+        ExecuteMsg::Pause { actions } => execute_pause(deps, env, info, actions),
+        ExecuteMsg::Unpause { actions } => execute_unpause(deps, env, info, actions),
+        ExecuteMsg::UpdatePauseGuardian { guardian } => {
+            execute_update_pause_guardian(deps, env, info, guardian)
+        }
+        ExecuteMsg::UpdateTransferAllowlist { add, remove } => {
+            execute_update_transfer_allowlist(deps, env, info, add, remove)
+        }
+        ExecuteMsg::OpenTrading {} => execute_open_trading(deps, env, info),
+        ExecuteMsg::UpdateLaunchLimits { max_tx_amount, max_wallet_balance } => {
+            execute_update_launch_limits(deps, env, info, max_tx_amount, max_wallet_balance)
+        }
+        ExecuteMsg::UpdateLimitExemptions { add, remove } => {
+            execute_update_limit_exemptions(deps, env, info, add, remove)
+        }
+        ExecuteMsg::Freeze { address, reason } => execute_freeze(deps, env, info, address, reason),
+        ExecuteMsg::Unfreeze { address } => execute_unfreeze(deps, env, info, address),
+        ExecuteMsg::UpdateComplianceAdmin { admin } => {
+            execute_update_compliance_admin(deps, env, info, admin)
+        }
+        ExecuteMsg::UpdateTransferFee { fee } => execute_update_transfer_fee(deps, env, info, fee),
+        ExecuteMsg::UpdateFeeExemptions { add, remove } => {
+            execute_update_fee_exemptions(deps, env, info, add, remove)
+        }
+        ExecuteMsg::TransferOwnership { new_owner, expires } => {
+            execute_transfer_ownership(deps, env, info, new_owner, expires)
+        }
+        ExecuteMsg::AcceptOwnership {} => execute_accept_ownership(deps, env, info),
+        ExecuteMsg::RenounceOwnership {} => execute_renounce_ownership(deps, env, info),
+        ExecuteMsg::GrantRole { role, address } => execute_grant_role(deps, env, info, role, address),
+        ExecuteMsg::RevokeRole { role, address } => {
+            execute_revoke_role(deps, env, info, role, address)
+        }
+        ExecuteMsg::RenounceRole { role } => execute_renounce_role(deps, env, info, role),
+        ExecuteMsg::SetRoleAdmin { role, admin_role } => {
+            execute_set_role_admin(deps, env, info, role, admin_role)
+        }
+        ExecuteMsg::UpdateTimelockDelay { delay } => {
+            execute_update_timelock_delay(deps, env, info, delay)
+        }
+        ExecuteMsg::ScheduleOperation { operation } => {
+            execute_schedule_operation(deps, env, info, operation)
+        }
+        ExecuteMsg::CancelOperation { id } => execute_cancel_operation(deps, env, info, id),
+        ExecuteMsg::ExecuteOperation { id } => execute_execute_operation(deps, env, info, id),
+        ExecuteMsg::Checkpoint {} => execute_checkpoint(deps, env, info),
+        ExecuteMsg::PruneBalanceHistory { before_height, limit } => {
+            execute_prune_balance_history(deps, env, info, before_height, limit)
+        }
+        ExecuteMsg::PruneSupplyHistory { before_height, limit } => {
+            execute_prune_supply_history(deps, env, info, before_height, limit)
+        }
+        ExecuteMsg::ContinueMigration { limit } =>
+            execute_continue_migration(deps, env, info, limit),
     }
 }
 
@@ -258,28 +613,92 @@ pub fn execute_transfer(
     recipient: String,
     amount: Uint128
 ) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, PausableAction::Transfer)?;
     let rcpt_addr = deps.api.addr_validate(&recipient)?;
+    assert_not_frozen(deps.storage, &[&info.sender, &rcpt_addr])?;
+    assert_transfer_allowed(deps.storage, &info.sender, &rcpt_addr)?;
 
-    BALANCES.update(
-        deps.storage,
-        &info.sender,
-        env.block.height,
-        |balance: Option<Uint128>| -> StdResult<_> {
-            Ok(balance.unwrap_or_default().checked_sub(amount)?)
-        }
-    )?;
-    BALANCES.update(
-        deps.storage,
-        &rcpt_addr,
-        env.block.height,
-        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) }
-    )?;
+    let fee = transfer_tokens(deps.storage, &env, &info.sender, &rcpt_addr, amount)?;
 
     let res = Response::new()
         .add_attribute("action", "transfer")
         .add_attribute("from", info.sender)
         .add_attribute("to", recipient)
-        .add_attribute("amount", amount);
+        .add_attribute("amount", amount)
+        .add_attribute("fee_amount", fee);
+    Ok(res)
+}
+
+pub fn execute_batch_transfer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfers: Vec<Cw20Coin>
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, PausableAction::Transfer)?;
+    if has_duplicates(transfers.iter().map(|t| &t.address)) {
+        return Err(ContractError::DuplicateRecipients {});
+    }
+
+    assert_not_frozen(deps.storage, &[&info.sender])?;
+
+    let total = batch_total(transfers.iter().map(|t| t.amount))?;
+    debit_balance(deps.storage, &env, &info.sender, total)?;
+
+    let mut res = Response::new()
+        .add_attribute("action", "batch_transfer")
+        .add_attribute("from", &info.sender)
+        .add_attribute("amount", total);
+    for transfer in transfers {
+        let rcpt_addr = deps.api.addr_validate(&transfer.address)?;
+        assert_not_frozen(deps.storage, &[&rcpt_addr])?;
+        assert_transfer_allowed(deps.storage, &info.sender, &rcpt_addr)?;
+        let fee = credit_transfer(deps.storage, &env, &info.sender, &rcpt_addr, transfer.amount)?;
+        res = res
+            .add_attribute("to", transfer.address)
+            .add_attribute("amount", transfer.amount)
+            .add_attribute("fee_amount", fee);
+    }
+    Ok(res)
+}
+
+pub fn execute_batch_send(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    sends: Vec<BatchSendItem>
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, PausableAction::Transfer)?;
+    if has_duplicates(sends.iter().map(|s| &s.contract)) {
+        return Err(ContractError::DuplicateRecipients {});
+    }
+
+    assert_not_frozen(deps.storage, &[&info.sender])?;
+
+    let total = batch_total(sends.iter().map(|s| s.amount))?;
+    debit_balance(deps.storage, &env, &info.sender, total)?;
+
+    let mut res = Response::new()
+        .add_attribute("action", "batch_send")
+        .add_attribute("from", &info.sender)
+        .add_attribute("amount", total);
+    for send in sends {
+        let rcpt_addr = deps.api.addr_validate(&send.contract)?;
+        assert_not_frozen(deps.storage, &[&rcpt_addr])?;
+        assert_transfer_allowed(deps.storage, &info.sender, &rcpt_addr)?;
+        let fee = credit_transfer(deps.storage, &env, &info.sender, &rcpt_addr, send.amount)?;
+        res = res
+            .add_attribute("to", &send.contract)
+            .add_attribute("amount", send.amount)
+            .add_attribute("fee_amount", fee)
+            .add_message(
+                (Cw20ReceiveMsg {
+                    sender: info.sender.to_string(),
+                    amount: send.amount - fee,
+                    msg: send.msg,
+                }).into_cosmos_msg(send.contract)?
+            );
+    }
     Ok(res)
 }
 
@@ -289,15 +708,11 @@ pub fn execute_burn(
     info: MessageInfo,
     amount: Uint128
 ) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, PausableAction::Burn)?;
+    assert_not_frozen(deps.storage, &[&info.sender])?;
+
     // lower balance
-    BALANCES.update(
-        deps.storage,
-        &info.sender,
-        env.block.height,
-        |balance: Option<Uint128>| -> StdResult<_> {
-            Ok(balance.unwrap_or_default().checked_sub(amount)?)
-        }
-    )?;
+    debit_balance(deps.storage, &env, &info.sender, amount)?;
     // reduce total_supply
     let token_info = TOKEN_INFO.update(
         deps.storage,
@@ -307,7 +722,7 @@ pub fn execute_burn(
         }
     )?;
 
-    capture_total_supply_history(deps.storage, &env, token_info.total_supply)?;
+    SUPPLY_HISTORY.record_burn(deps.storage, env.block.height, token_info.total_supply, amount)?;
 
     let res = Response::new()
         .add_attribute("action", "burn")
@@ -316,6 +731,36 @@ pub fn execute_burn(
     Ok(res)
 }
 
+/// Adds `amount` to the total supply, failing if that exceeds the cap.
+fn raise_supply(config: &mut TokenInfo, amount: Uint128) -> Result<(), ContractError> {
+    config.total_supply += amount;
+    if let Some(limit) = config.get_cap() {
+        if config.total_supply > limit {
+            return Err(ContractError::CannotExceedCap {});
+        }
+    }
+    Ok(())
+}
+
+/// Mints `amount` to `recipient` under the same cap, pause and freeze rules as `execute_mint`.
+/// Callers are responsible for checking who may mint.
+pub fn mint_tokens(
+    storage: &mut dyn Storage,
+    env: &Env,
+    recipient: &Addr,
+    amount: Uint128
+) -> Result<(), ContractError> {
+    let mut config = TOKEN_INFO.load(storage)?;
+    config.mint.as_ref().ok_or(ContractError::Unauthorized {})?;
+    assert_not_paused(storage, PausableAction::Mint)?;
+    assert_not_frozen(storage, &[recipient])?;
+
+    raise_supply(&mut config, amount)?;
+    TOKEN_INFO.save(storage, &config)?;
+    SUPPLY_HISTORY.record_mint(storage, env.block.height, config.total_supply, amount)?;
+    credit_balance(storage, env, recipient, amount)
+}
+
 pub fn execute_mint(
     deps: DepsMut,
     env: Env,
@@ -325,29 +770,30 @@ pub fn execute_mint(
 ) -> Result<Response, ContractError> {
     let mut config = TOKEN_INFO.may_load(deps.storage)?.ok_or(ContractError::Unauthorized {})?;
 
-    if config.mint.as_ref().ok_or(ContractError::Unauthorized {})?.minter != info.sender {
+    config.mint.as_ref().ok_or(ContractError::Unauthorized {})?;
+    let delegated = !has_role(deps.storage, Role::Minter, &info.sender)?;
+    if delegated && !MINTERS.has(deps.storage, &info.sender) {
         return Err(ContractError::Unauthorized {});
     }
+    // once mint approvals are enabled, no single key can mint on its own
+    if MINT_APPROVAL_CONFIG.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::MintApprovalRequired {});
+    }
+    assert_not_paused(deps.storage, PausableAction::Mint)?;
+    let rcpt_addr = deps.api.addr_validate(&recipient)?;
+    assert_not_frozen(deps.storage, &[&info.sender, &rcpt_addr])?;
 
-    // update supply and enforce cap
-    config.total_supply += amount;
-    if let Some(limit) = config.get_cap() {
-        if config.total_supply > limit {
-            return Err(ContractError::CannotExceedCap {});
-        }
+    raise_supply(&mut config, amount)?;
+    // the token minter is only bound by the cap, delegated minters by their own limits as well
+    if delegated {
+        consume_minter_allowance(deps.storage, &env.block, &info.sender, amount)?;
     }
     TOKEN_INFO.save(deps.storage, &config)?;
 
-    capture_total_supply_history(deps.storage, &env, config.total_supply)?;
+    SUPPLY_HISTORY.record_mint(deps.storage, env.block.height, config.total_supply, amount)?;
 
     // add amount to recipient balance
-    let rcpt_addr = deps.api.addr_validate(&recipient)?;
-    BALANCES.update(
-        deps.storage,
-        &rcpt_addr,
-        env.block.height,
-        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) }
-    )?;
+    credit_balance(deps.storage, &env, &rcpt_addr, amount)?;
 
     let res = Response::new()
         .add_attribute("action", "mint")
@@ -364,33 +810,24 @@ pub fn execute_send(
     amount: Uint128,
     msg: Binary
 ) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, PausableAction::Transfer)?;
     let rcpt_addr = deps.api.addr_validate(&contract)?;
+    assert_not_frozen(deps.storage, &[&info.sender, &rcpt_addr])?;
+    assert_transfer_allowed(deps.storage, &info.sender, &rcpt_addr)?;
 
     // move the tokens to the contract
-    BALANCES.update(
-        deps.storage,
-        &info.sender,
-        env.block.height,
-        |balance: Option<Uint128>| -> StdResult<_> {
-            Ok(balance.unwrap_or_default().checked_sub(amount)?)
-        }
-    )?;
-    BALANCES.update(
-        deps.storage,
-        &rcpt_addr,
-        env.block.height,
-        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) }
-    )?;
+    let fee = transfer_tokens(deps.storage, &env, &info.sender, &rcpt_addr, amount)?;
 
     let res = Response::new()
         .add_attribute("action", "send")
         .add_attribute("from", &info.sender)
         .add_attribute("to", &contract)
         .add_attribute("amount", amount)
+        .add_attribute("fee_amount", fee)
         .add_message(
             (Cw20ReceiveMsg {
                 sender: info.sender.into(),
-                amount,
+                amount: amount - fee,
                 msg,
             }).into_cosmos_msg(contract)?
         );
@@ -405,30 +842,22 @@ pub fn execute_update_minter(
 ) -> Result<Response, ContractError> {
     let mut config = TOKEN_INFO.may_load(deps.storage)?.ok_or(ContractError::Unauthorized {})?;
 
-    let mint = config.mint.as_ref().ok_or(ContractError::Unauthorized {})?;
-    if mint.minter != info.sender {
-        return Err(ContractError::Unauthorized {});
-    }
+    assert_primary_holder(deps.storage, Role::Minter, &info.sender)?;
 
-    let minter_data = new_minter
-        .map(|new_minter| deps.api.addr_validate(&new_minter))
-        .transpose()?
-        .map(|minter| MinterData {
-            minter,
-            cap: mint.cap,
-        });
+    // handing over to another address is only possible with a proposal it accepts
+    if new_minter.is_some() {
+        return Err(ContractError::MinterHandoverRequired {});
+    }
 
-    config.mint = minter_data;
+    config.mint = None;
 
     TOKEN_INFO.save(deps.storage, &config)?;
+    PENDING_MINTER.remove(deps.storage);
 
     Ok(
         Response::default()
             .add_attribute("action", "update_minter")
-            .add_attribute(
-                "new_minter",
-                config.mint.map(|m| m.minter.into_string()).unwrap_or_else(|| "None".to_string())
-            )
+            .add_attribute("new_minter", "None")
     )
 }
 
@@ -440,13 +869,11 @@ pub fn execute_update_marketing(
     description: Option<String>,
     marketing: Option<String>
 ) -> Result<Response, ContractError> {
-    let mut marketing_info = MARKETING_INFO.may_load(deps.storage)?.ok_or(
-        ContractError::Unauthorized {}
-    )?;
-
-    if marketing_info.marketing.as_ref().ok_or(ContractError::Unauthorized {})? != &info.sender {
-        return Err(ContractError::Unauthorized {});
+    assert_role(deps.storage, Role::Marketing, &info.sender)?;
+    if marketing.is_some() {
+        assert_primary_holder(deps.storage, Role::Marketing, &info.sender)?;
     }
+    let mut marketing_info = MARKETING_INFO.may_load(deps.storage)?.unwrap_or_default();
 
     match project {
         Some(empty) if empty.trim().is_empty() => {
@@ -499,15 +926,10 @@ pub fn execute_upload_logo(
     info: MessageInfo,
     logo: Logo
 ) -> Result<Response, ContractError> {
-    let mut marketing_info = MARKETING_INFO.may_load(deps.storage)?.ok_or(
-        ContractError::Unauthorized {}
-    )?;
-
     verify_logo(&logo)?;
 
-    if marketing_info.marketing.as_ref().ok_or(ContractError::Unauthorized {})? != &info.sender {
-        return Err(ContractError::Unauthorized {});
-    }
+    assert_role(deps.storage, Role::Marketing, &info.sender)?;
+    let mut marketing_info = MARKETING_INFO.may_load(deps.storage)?.unwrap_or_default();
 
     LOGO.save(deps.storage, &logo)?;
 
@@ -523,57 +945,282 @@ pub fn execute_upload_logo(
     Ok(res)
 }
 
-/// Snapshots the total token supply at current block.
-///
-/// * **total_supply** current token total supply.
-pub fn capture_total_supply_history(
+/// Lowers the balance of `address`, failing if that would dip into tokens still locked by
+/// its vesting schedule.
+pub fn debit_balance(
     storage: &mut dyn Storage,
     env: &Env,
-    total_supply: Uint128
-) -> StdResult<()> {
-    TOTAL_SUPPLY_HISTORY.save(storage, env.block.height, &total_supply)
+    address: &Addr,
+    amount: Uint128
+) -> Result<(), ContractError> {
+    let locked = locked_amount(storage, &env.block, address)?;
+    let balance = balances(storage)?.update(
+        storage,
+        address,
+        env.block.height,
+        |balance: Option<Uint128>| -> Result<_, ContractError> {
+            let balance = balance.unwrap_or_default().checked_sub(amount).map_err(StdError::overflow)?;
+            if balance < locked {
+                return Err(ContractError::VestingLocked {});
+            }
+            Ok(balance)
+        }
+    )?;
+    if balance.is_zero() && !amount.is_zero() {
+        update_holder_count(storage, env, address, false)?;
+    }
+    update_voting_power(storage, env.block.height, address, amount, false)?;
+    Ok(())
 }
 
-/// Returns the total token supply at the given block.
-pub fn get_total_supply_at(storage: &dyn Storage, block: u64) -> StdResult<Uint128> {
-    // Look for the last value recorded before the current block (if none then value is zero)
-    let end = Bound::inclusive(block);
-    let last_value_up_to_block = TOTAL_SUPPLY_HISTORY.range(
+/// Raises the balance of `address`, failing if that breaks one of the launch limits.
+pub fn credit_balance(
+    storage: &mut dyn Storage,
+    env: &Env,
+    address: &Addr,
+    amount: Uint128
+) -> Result<(), ContractError> {
+    let limits = limits_for(storage, address)?;
+    credit_balance_within(storage, env, address, amount, &limits)
+}
+
+/// Raises the balance of `address`, failing if that breaks `limits`.
+fn credit_balance_within(
+    storage: &mut dyn Storage,
+    env: &Env,
+    address: &Addr,
+    amount: Uint128,
+    limits: &LaunchLimits
+) -> Result<(), ContractError> {
+    let balance = balances(storage)?.update(
         storage,
-        None,
-        Some(end),
-        Order::Descending
-    ).next();
+        address,
+        env.block.height,
+        |balance: Option<Uint128>| -> Result<_, ContractError> {
+            let balance = balance.unwrap_or_default() + amount;
+            limits.check(amount, balance)?;
+            Ok(balance)
+        }
+    )?;
+    if balance == amount && !amount.is_zero() {
+        update_holder_count(storage, env, address, true)?;
+    }
+    update_voting_power(storage, env.block.height, address, amount, true)?;
+    Ok(())
+}
 
-    if let Some(value) = last_value_up_to_block {
-        let (_, v) = value?;
-        return Ok(v);
+/// Counts `address` in or out of the holders when its balance leaves or reaches zero.
+fn update_holder_count(
+    storage: &mut dyn Storage,
+    env: &Env,
+    address: &Addr,
+    joined: bool
+) -> StdResult<()> {
+    if !holder_counted(storage, address)? {
+        return Ok(());
+    }
+    HOLDER_COUNT.update(storage, env.block.height, |count| -> StdResult<_> {
+        let count = count.unwrap_or_default();
+        Ok(if joined { count + 1 } else { count.saturating_sub(1) })
+    })?;
+    Ok(())
+}
+
+/// Moves `amount` from `from` to `to`, charging the transfer fee unless either side is exempt.
+/// Returns the part of `amount` that was taken as fee.
+pub fn transfer_tokens(
+    storage: &mut dyn Storage,
+    env: &Env,
+    from: &Addr,
+    to: &Addr,
+    amount: Uint128
+) -> Result<Uint128, ContractError> {
+    debit_balance(storage, env, from, amount)?;
+    credit_transfer(storage, env, from, to, amount)
+}
+
+/// Credits `to` with `amount` already debited from `from`, less the transfer fee. The fee is
+/// credited to the fee collector, or burned when there is none.
+/// Returns the part of `amount` that was taken as fee.
+fn credit_transfer(
+    storage: &mut dyn Storage,
+    env: &Env,
+    from: &Addr,
+    to: &Addr,
+    amount: Uint128
+) -> Result<Uint128, ContractError> {
+    let (fee, collector) = transfer_fee(storage, from, to, amount)?;
+    let received = amount - fee;
+    // the transaction limit covers the whole amount sent, fee included
+    let limits = limits_for(storage, to)?;
+    limits.check_tx_amount(amount)?;
+    let limits = LaunchLimits { max_tx_amount: None, ..limits };
+    credit_balance_within(storage, env, to, received, &limits)?;
+
+    if !fee.is_zero() {
+        match collector {
+            // fees pile up at the collector, so the launch limits do not apply to it
+            Some(collector) => {
+                credit_balance_within(storage, env, &collector, fee, &LaunchLimits::default())?;
+            }
+            None => {
+                let token_info = TOKEN_INFO.update(
+                    storage,
+                    |mut info| -> StdResult<_> {
+                        info.total_supply = info.total_supply.checked_sub(fee)?;
+                        Ok(info)
+                    }
+                )?;
+                SUPPLY_HISTORY.record_burn(storage, env.block.height, token_info.total_supply, fee)?;
+            }
+        }
     }
 
-    Ok(Uint128::zero())
+    Ok(fee)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrate { previous_contract: stored.contract });
+    }
+
+    let previous_version: Version = stored.version.parse()?;
+    let new_version: Version = CONTRACT_VERSION.parse()?;
+    if previous_version > new_version {
+        return Err(ContractError::CannotMigrateVersion { previous_version: stored.version });
+    }
+
+    migrate_state(deps.storage, &previous_version)?;
+    if let Some(owner) = msg.owner {
+        OWNER.save(deps.storage, &Ownership {
+            owner: Some(deps.api.addr_validate(&owner)?),
+            ..Ownership::default()
+        })?;
+    }
+    let remaining = run_pending_migrations(
+        deps.storage,
+        env.block.height,
+        msg.batch_limit.unwrap_or(DEFAULT_MIGRATION_LIMIT)
+    )?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let res = Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION)
+        .add_attribute("remaining_steps", remaining.to_string());
+    Ok(res)
+}
+
+/// Runs the state migrations required to bring storage written by `previous_version` up to
+/// the current layout. Each step is gated on the version that introduced the change, so
+/// migrating from an older release runs every step in order. Steps that touch an unbounded
+/// number of entries are only scheduled here and processed in pages.
+fn migrate_state(storage: &mut dyn Storage, previous_version: &Version) -> StdResult<()> {
+    // 0.1.0 replaced the hand-synchronised `allowance_spender` map with a spender index
+    if previous_version < &Version::new(0, 1, 0) {
+        schedule_migration(storage, vec![
+            MigrationStep::AllowanceIndexBackfill { start_after: None },
+            MigrationStep::LegacyAllowanceCleanup
+        ])?;
+    }
+    // 0.2.0 started tracking the number of holders
+    if previous_version < &Version::new(0, 2, 0) {
+        schedule_migration(storage, vec![MigrationStep::HolderCountBackfill { start_after: None }])?;
+    }
+    // 0.3.0 records minted and burned amounts along with the total supply
+    if previous_version < &Version::new(0, 3, 0) && !LEGACY_TOTAL_SUPPLY_HISTORY.is_empty(storage) {
+        schedule_migration(storage, vec![MigrationStep::SupplyHistoryBackfill])?;
+    }
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Balance { address } => to_binary(&query_balance(deps, address)?),
         QueryMsg::BalanceAt { address, height } =>
             to_binary(&query_balance_at(deps, address, height)?),
         QueryMsg::TokenInfo {} => to_binary(&query_token_info(deps)?),
-        QueryMsg::TotalSupplyAt { block } => to_binary(&get_total_supply_at(deps.storage, block)?),
+        QueryMsg::TotalSupplyAt { block } => to_binary(&SUPPLY_HISTORY.at(deps.storage, block)?),
+        QueryMsg::SupplyHistory { start_after, end_before, limit, order } => {
+            to_binary(&query_supply_history(deps, start_after, end_before, limit, order)?)
+        }
+        QueryMsg::HolderCountAt { height } => to_binary(&query_holder_count_at(deps, height)?),
+        QueryMsg::BalanceHistory {} => to_binary(&query_balance_history(deps)?),
+        QueryMsg::Nonce { owner } => to_binary(&query_nonce(deps, owner)?),
+        QueryMsg::Delegation { address } => to_binary(&query_delegation(deps, address)?),
+        QueryMsg::VotingPower { address } => to_binary(&query_voting_power(deps, address)?),
+        QueryMsg::VotingPowerAt { address, height } => {
+            to_binary(&query_voting_power_at(deps, address, height)?)
+        }
+        QueryMsg::TotalVotingPowerAt { height } => {
+            to_binary(&query_total_voting_power_at(deps, height)?)
+        }
         QueryMsg::Minter {} => to_binary(&query_minter(deps)?),
+        QueryMsg::VestingSchedule { address } => {
+            to_binary(&query_vesting_schedule(deps, env, address)?)
+        }
+        QueryMsg::SpendableBalance { address } => {
+            to_binary(&query_spendable_balance(deps, env, address)?)
+        }
+        QueryMsg::PendingMinter {} => to_binary(&query_pending_minter(deps)?),
+        QueryMsg::AllMinters { start_after, limit } => {
+            to_binary(&query_all_minters(deps, start_after, limit)?)
+        }
+        QueryMsg::MintApproval {} => to_binary(&query_mint_approval(deps)?),
+        QueryMsg::MintProposal { id } => to_binary(&query_mint_proposal(deps, id)?),
+        QueryMsg::MintProposals { start_after, limit } => {
+            to_binary(&query_mint_proposals(deps, start_after, limit)?)
+        }
         QueryMsg::Allowance { owner, spender } => {
-            to_binary(&query_allowance(deps, owner, spender)?)
+            to_binary(&query_allowance(deps, env, owner, spender)?)
+        }
+        QueryMsg::AllowanceAt { owner, spender, height } => {
+            to_binary(&query_allowance_at(deps, owner, spender, height)?)
+        }
+        QueryMsg::AllAllowances { owner, start_after, limit, include_expired } => {
+            let expired_at = (!include_expired.unwrap_or(true)).then_some(&env.block);
+            to_binary(&query_owner_allowances(deps, owner, start_after, limit, expired_at)?)
+        }
+        QueryMsg::AllSpenderAllowances { spender, start_after, limit, include_expired } => {
+            let expired_at = (!include_expired.unwrap_or(true)).then_some(&env.block);
+            to_binary(&query_spender_allowances(deps, spender, start_after, limit, expired_at)?)
         }
-        QueryMsg::AllAllowances { owner, start_after, limit } =>
-            to_binary(&query_owner_allowances(deps, owner, start_after, limit)?),
-        QueryMsg::AllSpenderAllowances { spender, start_after, limit } =>
-            to_binary(&query_spender_allowances(deps, spender, start_after, limit)?),
         QueryMsg::AllAccounts { start_after, limit } => {
             to_binary(&query_all_accounts(deps, start_after, limit)?)
         }
         QueryMsg::MarketingInfo {} => to_binary(&query_marketing_info(deps)?),
         QueryMsg::DownloadLogo {} => to_binary(&query_download_logo(deps)?),
+        QueryMsg::HasRole { role, address } => to_binary(&query_has_role(deps, role, address)?),
+        QueryMsg::RoleMembers { role, start_after, limit } => {
+            to_binary(&query_role_members(deps, role, start_after, limit)?)
+        }
+        QueryMsg::Timelock {} => to_binary(&query_timelock(deps)?),
+        QueryMsg::PendingOperations { start_after, limit } => {
+            to_binary(&query_pending_operations(deps, start_after, limit)?)
+        }
+        QueryMsg::Ownership {} => to_binary(&query_ownership(deps)?),
+        QueryMsg::PauseInfo {} => to_binary(&query_pause_info(deps)?),
+        QueryMsg::TransferRestriction {} => to_binary(&query_transfer_restriction(deps)?),
+        QueryMsg::TransferAllowlist { start_after, limit } => {
+            to_binary(&query_transfer_allowlist(deps, start_after, limit)?)
+        }
+        QueryMsg::LaunchLimits {} => to_binary(&query_launch_limits(deps)?),
+        QueryMsg::LimitExemptions { start_after, limit } => {
+            to_binary(&query_limit_exemptions(deps, start_after, limit)?)
+        }
+        QueryMsg::IsFrozen { address } => to_binary(&query_is_frozen(deps, address)?),
+        QueryMsg::AllFrozen { start_after, limit } => {
+            to_binary(&query_all_frozen(deps, start_after, limit)?)
+        }
+        QueryMsg::TransferFee {} => to_binary(&query_transfer_fee(deps)?),
+        QueryMsg::FeeExemptions { start_after, limit } => {
+            to_binary(&query_fee_exemptions(deps, start_after, limit)?)
+        }
     }
 }
 
@@ -585,7 +1232,7 @@ pub fn query_balance(deps: Deps, address: String) -> StdResult<BalanceResponse>
 
 pub fn query_balance_at(deps: Deps, address: String, block: u64) -> StdResult<BalanceResponse> {
     let address = deps.api.addr_validate(&address)?;
-    let balance = BALANCES.may_load_at_height(deps.storage, &address, block)?.unwrap_or_default();
+    let balance = balance_at(deps.storage, &address, block)?.unwrap_or_default();
     Ok(BalanceResponse { balance })
 }
 
@@ -600,6 +1247,14 @@ pub fn query_token_info(deps: Deps) -> StdResult<TokenInfoResponse> {
     Ok(res)
 }
 
+pub fn query_holder_count_at(deps: Deps, height: u64) -> StdResult<HolderCountResponse> {
+    if holder_count_pending(deps.storage)? {
+        return Err(StdError::generic_err("Holder count is still being migrated"));
+    }
+    let count = HOLDER_COUNT.may_load_at_height(deps.storage, height)?.unwrap_or_default();
+    Ok(HolderCountResponse { count })
+}
+
 pub fn query_minter(deps: Deps) -> StdResult<Option<MinterResponse>> {
     let meta = TOKEN_INFO.load(deps.storage)?;
     let minter = match meta.mint {
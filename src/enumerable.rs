@@ -1,31 +1,73 @@
-use cosmwasm_std::{ Deps, Order, StdResult };
+use cosmwasm_std::{ Addr, BlockInfo, Deps, Order, StdError, StdResult };
 use cw20::{
     AllAccountsResponse,
+    AllowanceResponse,
     AllAllowancesResponse,
     AllSpenderAllowancesResponse,
     AllowanceInfo,
     SpenderAllowanceInfo,
 };
 
-use crate::state::{ ALLOWANCES, ALLOWANCES_SPENDER, BALANCES };
+use crate::migrations::{ allowance_index_pending, supply_history_pending };
+use crate::mint_proposals::mint_proposal_response;
+use crate::msg::{
+    AllFrozenResponse,
+    AllMintersResponse,
+    FeeExemptionsResponse,
+    FrozenAccount,
+    IsFrozenResponse,
+    LimitExemptionsResponse,
+    MinterInfo,
+    MintProposalsResponse,
+    OrderBy,
+    SupplyHistoryEntry,
+    SupplyHistoryResponse,
+    PendingOperation,
+    PendingOperationsResponse,
+    RoleMembersResponse,
+    TransferAllowlistResponse,
+};
+use crate::roles::{ primary_holder, role_admin };
+use crate::state::{
+    allowances,
+    BALANCES,
+    FEE_EXEMPT,
+    FROZEN,
+    LIMIT_EXEMPT,
+    MINTERS,
+    MINT_PROPOSALS,
+    OPERATIONS,
+    ROLES,
+    SUPPLY_HISTORY,
+    TRANSFER_ALLOWLIST,
+    Role,
+};
 use cw_storage_plus::Bound;
 
 // settings for pagination
-const MAX_LIMIT: u32 = 30;
-const DEFAULT_LIMIT: u32 = 10;
+pub(crate) const MAX_LIMIT: u32 = 30;
+pub(crate) const DEFAULT_LIMIT: u32 = 10;
+/// Leaving out expired allowances scans at most this many entries per requested one
+const EXPIRED_SCAN_FACTOR: usize = 10;
 
+/// Lists the allowances of `owner`. When `expired_at` is given, allowances expired at that
+/// block are skipped.
 pub fn query_owner_allowances(
     deps: Deps,
     owner: String,
     start_after: Option<String>,
-    limit: Option<u32>
+    limit: Option<u32>,
+    expired_at: Option<&BlockInfo>
 ) -> StdResult<AllAllowancesResponse> {
     let owner_addr = deps.api.addr_validate(&owner)?;
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
     let start = start_after.map(|s| Bound::ExclusiveRaw(s.into_bytes()));
 
-    let allowances = ALLOWANCES.prefix(&owner_addr)
+    let allowances = allowances()
+        .prefix(&owner_addr)
         .range(deps.storage, start, None, Order::Ascending)
+        .take(scan_limit(limit, expired_at))
+        .filter(|item| !is_expired(item, expired_at))
         .take(limit)
         .map(|item| {
             item.map(|(addr, allow)| AllowanceInfo {
@@ -38,22 +80,32 @@ pub fn query_owner_allowances(
     Ok(AllAllowancesResponse { allowances })
 }
 
+/// Lists the allowances granted to `spender`. When `expired_at` is given, allowances expired
+/// at that block are skipped.
 pub fn query_spender_allowances(
     deps: Deps,
     spender: String,
     start_after: Option<String>,
-    limit: Option<u32>
+    limit: Option<u32>,
+    expired_at: Option<&BlockInfo>
 ) -> StdResult<AllSpenderAllowancesResponse> {
     let spender_addr = deps.api.addr_validate(&spender)?;
+    if allowance_index_pending(deps.storage)? {
+        return Err(StdError::generic_err("Spender allowance index is still being migrated"));
+    }
+
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into_bytes()));
+    let start = start_after.map(|s| Bound::exclusive((Addr::unchecked(s), spender_addr.clone())));
 
-    let allowances = ALLOWANCES_SPENDER.prefix(&spender_addr)
+    let allowances = allowances()
+        .idx.spender.prefix(spender_addr)
         .range(deps.storage, start, None, Order::Ascending)
+        .take(scan_limit(limit, expired_at))
+        .filter(|item| !is_expired(item, expired_at))
         .take(limit)
         .map(|item| {
-            item.map(|(addr, allow)| SpenderAllowanceInfo {
-                owner: addr.into(),
+            item.map(|((owner, _), allow)| SpenderAllowanceInfo {
+                owner: owner.into(),
                 allowance: allow.allowance,
                 expires: allow.expires,
             })
@@ -62,6 +114,21 @@ pub fn query_spender_allowances(
     Ok(AllSpenderAllowancesResponse { allowances })
 }
 
+/// How many entries a page may scan, bounded while expired ones are left out.
+fn scan_limit(limit: usize, expired_at: Option<&BlockInfo>) -> usize {
+    match expired_at {
+        Some(_) => limit * EXPIRED_SCAN_FACTOR,
+        None => limit,
+    }
+}
+
+fn is_expired<K>(item: &StdResult<(K, AllowanceResponse)>, block: Option<&BlockInfo>) -> bool {
+    match (item, block) {
+        (Ok((_, allowance)), Some(block)) => allowance.expires.is_expired(block),
+        _ => false,
+    }
+}
+
 pub fn query_all_accounts(
     deps: Deps,
     start_after: Option<String>,
@@ -78,6 +145,201 @@ pub fn query_all_accounts(
     Ok(AllAccountsResponse { accounts })
 }
 
+pub fn query_all_minters(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>
+) -> StdResult<AllMintersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+    let minters = MINTERS.range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(minter, allowance)| MinterInfo {
+                minter: minter.into(),
+                cap: allowance.cap,
+                minted: allowance.minted,
+                rate_limit: allowance.rate_limit,
+                expires: allowance.expires,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(AllMintersResponse { minters })
+}
+
+pub fn query_is_frozen(deps: Deps, address: String) -> StdResult<IsFrozenResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let reason = FROZEN.may_load(deps.storage, &address)?;
+    Ok(IsFrozenResponse { frozen: reason.is_some(), reason })
+}
+
+pub fn query_all_frozen(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>
+) -> StdResult<AllFrozenResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+    let accounts = FROZEN.range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(address, reason)| FrozenAccount {
+                address: address.into(),
+                reason,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(AllFrozenResponse { accounts })
+}
+
+pub fn query_transfer_allowlist(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>
+) -> StdResult<TransferAllowlistResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+    let addresses = TRANSFER_ALLOWLIST.keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(Into::into))
+        .collect::<StdResult<_>>()?;
+
+    Ok(TransferAllowlistResponse { addresses })
+}
+
+pub fn query_limit_exemptions(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>
+) -> StdResult<LimitExemptionsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+    let addresses = LIMIT_EXEMPT.keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(Into::into))
+        .collect::<StdResult<_>>()?;
+
+    Ok(LimitExemptionsResponse { addresses })
+}
+
+pub fn query_role_members(
+    deps: Deps,
+    role: Role,
+    start_after: Option<String>,
+    limit: Option<u32>
+) -> StdResult<RoleMembersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+    let members = ROLES.prefix(role.as_str())
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(Into::into))
+        .collect::<StdResult<_>>()?;
+
+    Ok(RoleMembersResponse {
+        primary: primary_holder(deps.storage, role)?.map(Into::into),
+        members,
+        admin_role: role_admin(deps.storage, role)?,
+    })
+}
+
+pub fn query_pending_operations(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>
+) -> StdResult<PendingOperationsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let operations = OPERATIONS.range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(id, scheduled)| PendingOperation {
+                id,
+                operation: scheduled.operation,
+                proposer: scheduled.proposer.into(),
+                eta: scheduled.eta,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(PendingOperationsResponse { operations })
+}
+
+pub fn query_mint_proposals(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>
+) -> StdResult<MintProposalsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let proposals = MINT_PROPOSALS.range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(id, proposal)| mint_proposal_response(id, proposal)))
+        .collect::<StdResult<_>>()?;
+
+    Ok(MintProposalsResponse { proposals })
+}
+
+pub fn query_supply_history(
+    deps: Deps,
+    start_after: Option<u64>,
+    end_before: Option<u64>,
+    limit: Option<u32>,
+    order: Option<OrderBy>
+) -> StdResult<SupplyHistoryResponse> {
+    // legacy entries are left out until they have been moved over
+    if supply_history_pending(deps.storage)? {
+        return Err(StdError::generic_err("Supply history is still being migrated"));
+    }
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let end = end_before.map(Bound::exclusive);
+    let order = order.unwrap_or(OrderBy::Ascending).into();
+
+    let entries = SUPPLY_HISTORY.records()
+        .range(deps.storage, start, end, order)
+        .take(limit)
+        .map(|item| {
+            item.map(|(height, record)| SupplyHistoryEntry {
+                height,
+                total_supply: record.total_supply,
+                minted: record.minted,
+                burned: record.burned,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(SupplyHistoryResponse {
+        entries,
+        pruned_before: SUPPLY_HISTORY.pruned_before(deps.storage)?,
+    })
+}
+
+pub fn query_fee_exemptions(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>
+) -> StdResult<FeeExemptionsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+    let addresses = FEE_EXEMPT.keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(Into::into))
+        .collect::<StdResult<_>>()?;
+
+    Ok(FeeExemptionsResponse { addresses })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -91,7 +353,7 @@ mod tests {
 
     // this will set up the instantiation for other tests
     fn do_instantiate(mut deps: DepsMut, addr: &str, amount: Uint128) -> TokenInfoResponse {
-        let instantiate_msg = classic_terraport::token::InstantiateMsg {
+        let instantiate_msg = crate::msg::InstantiateMsg {
             name: "Auto Gen".to_string(),
             symbol: "AUTO".to_string(),
             decimals: 3,
@@ -101,6 +363,7 @@ mod tests {
             }],
             mint: None,
             marketing: None,
+            ..Default::default()
         };
         let info = mock_info("creator", &[]);
         let env = mock_env();
@@ -122,7 +385,7 @@ mod tests {
         do_instantiate(deps.as_mut(), &owner, Uint128::new(12340000));
 
         // no allowance to start
-        let allowances = query_owner_allowances(deps.as_ref(), owner.clone(), None, None).unwrap();
+        let allowances = query_owner_allowances(deps.as_ref(), owner.clone(), None, None, None).unwrap();
         assert_eq!(allowances.allowances, vec![]);
 
         // set allowance with height expiration
@@ -145,7 +408,7 @@ mod tests {
         execute(deps.as_mut(), env, info, msg).unwrap();
 
         // query list gets 2
-        let allowances = query_owner_allowances(deps.as_ref(), owner.clone(), None, None).unwrap();
+        let allowances = query_owner_allowances(deps.as_ref(), owner.clone(), None, None, None).unwrap();
         assert_eq!(allowances.allowances.len(), 2);
 
         // first one is spender1 (order of CanonicalAddr uncorrelated with String)
@@ -153,7 +416,8 @@ mod tests {
             deps.as_ref(),
             owner.clone(),
             None,
-            Some(1)
+            Some(1),
+            None
         ).unwrap();
         assert_eq!(allowances.allowances.len(), 1);
         let allow = &allowances.allowances[0];
@@ -166,7 +430,8 @@ mod tests {
             deps.as_ref(),
             owner,
             Some(allow.spender.clone()),
-            Some(10000)
+            Some(10000),
+            None
         ).unwrap();
         assert_eq!(allowances.allowances.len(), 1);
         let allow = &allowances.allowances[0];
@@ -175,6 +440,48 @@ mod tests {
         assert_eq!(&allow.allowance, &allow2);
     }
 
+    #[test]
+    fn expired_allowances_can_be_left_out() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+        let owner = String::from("owner");
+        let info = mock_info(owner.as_ref(), &[]);
+        let mut env = mock_env();
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(12340000));
+
+        let expiring = Expiration::AtHeight(env.block.height + 10);
+        for (spender, expires) in [("earlier", Some(expiring)), ("later", None)] {
+            let msg = ExecuteMsg::IncreaseAllowance {
+                spender: spender.into(),
+                amount: Uint128::new(100),
+                expires,
+            };
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        env.block.height += 10;
+        let all = query_owner_allowances(deps.as_ref(), owner.clone(), None, None, None).unwrap();
+        assert_eq!(all.allowances.len(), 2);
+        let active = query_owner_allowances(deps.as_ref(), owner.clone(), None, None, Some(&env.block));
+        let active = active.unwrap().allowances;
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].spender, "later");
+
+        let active = query_spender_allowances(deps.as_ref(), "earlier".into(), None, None, Some(&env.block));
+        assert_eq!(active.unwrap().allowances, vec![]);
+
+        // anyone can clean up the expired entry, one page of allowances at a time
+        let msg = ExecuteMsg::PruneExpiredAllowances { owner: owner.clone(), limit: Some(1) };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg.clone()).unwrap();
+        assert_eq!(res.attributes[2], ("count", "1"));
+        assert_eq!(res.attributes[3], ("done", "false"));
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg.clone()).unwrap();
+        assert_eq!(res.attributes[2], ("count", "0"));
+        let res = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(res.attributes[3], ("done", "true"));
+        let all = query_owner_allowances(deps.as_ref(), owner, None, None, None).unwrap();
+        assert_eq!(all.allowances.len(), 1);
+    }
+
     #[test]
     fn query_all_spender_allowances_works() {
         let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
@@ -193,6 +500,7 @@ mod tests {
             deps.as_ref(),
             spender.clone(),
             None,
+            None,
             None
         ).unwrap();
         assert_eq!(allowances.allowances, vec![]);
@@ -225,6 +533,7 @@ mod tests {
             spender: spender.clone(),
             start_after: None,
             limit: None,
+            include_expired: None,
         };
         let allowances: AllSpenderAllowancesResponse = from_binary(
             &query(deps.as_ref(), env.clone(), msg).unwrap()
@@ -236,6 +545,7 @@ mod tests {
             spender: spender.clone(),
             start_after: None,
             limit: Some(1),
+            include_expired: None,
         };
         let allowances: AllSpenderAllowancesResponse = from_binary(
             &query(deps.as_ref(), env.clone(), msg).unwrap()
@@ -251,6 +561,7 @@ mod tests {
             spender,
             start_after: Some(owner1),
             limit: Some(10000),
+            include_expired: None,
         };
         let allowances: AllSpenderAllowancesResponse = from_binary(
             &query(deps.as_ref(), env, msg).unwrap()
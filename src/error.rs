@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{ StdError, Uint128 };
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -26,6 +26,90 @@ pub enum ContractError {
     #[error("Minting cannot exceed the cap")]
     CannotExceedCap {},
 
+    #[error("Minting cannot exceed the minter's own cap")]
+    MinterCapExceeded {},
+
+    #[error("Minting cannot exceed the minter's rate limit")]
+    MintRateLimitExceeded {},
+
+    #[error("Minting rights have expired")]
+    MinterExpired {},
+
+    #[error("Mint cap cannot be below the current total supply")]
+    CapBelowSupply {},
+
+    #[error("Mint cap cannot be raised above its ceiling")]
+    CapAboveCeiling {},
+
+    #[error("Minting rights must be handed over with ProposeMinter and AcceptMinter")]
+    MinterHandoverRequired {},
+
+    #[error("No minter handover is pending")]
+    NoPendingMinter {},
+
+    #[error("The minter handover proposal has expired")]
+    MinterProposalExpired {},
+
+    #[error("Role was not granted to this address")]
+    RoleNotGranted {},
+
+    #[error("This change has to be scheduled through the timelock")]
+    TimelockRequired {},
+
+    #[error("No scheduled operation with this id")]
+    UnknownOperation {},
+
+    #[error("The operation cannot be executed before {eta}")]
+    OperationNotReady { eta: String },
+
+    #[error("No ownership transfer is pending")]
+    NoPendingOwner {},
+
+    #[error("The ownership transfer has expired")]
+    OwnershipTransferExpired {},
+
+    #[error("Mint rate limit period must be greater than zero")]
+    InvalidMintRateLimit {},
+
+    #[error("Mint approval threshold must be between one and the number of mint signers")]
+    InvalidMintThreshold {},
+
+    #[error("Mint approvals are not enabled")]
+    MintApprovalDisabled {},
+
+    #[error("Minting needs the approval of the mint signers")]
+    MintApprovalRequired {},
+
+    #[error("No mint proposal with this id")]
+    UnknownMintProposal {},
+
+    #[error("The mint proposal has expired")]
+    MintProposalExpired {},
+
+    #[error("The mint proposal was already executed")]
+    MintProposalExecuted {},
+
+    #[error("Already approved this mint proposal")]
+    AlreadyApproved {},
+
+    #[error("Cannot move tokens that are still locked by vesting")]
+    VestingLocked {},
+
+    #[error("Invalid vesting schedule")]
+    InvalidVestingSchedule {},
+
+    #[error("Address already has a vesting schedule")]
+    VestingAlreadyExists {},
+
+    #[error("Allowance changed, current allowance is {current}")]
+    AllowanceMismatch { current: Uint128 },
+
+    #[error("Invalid permit signature")]
+    InvalidPermitSignature {},
+
+    #[error("Invalid permit nonce, expected {expected}")]
+    InvalidPermitNonce { expected: u64 },
+
     #[error("Logo binary data exceeds 5KB limit")]
     LogoTooBig {},
 
@@ -40,4 +124,59 @@ pub enum ContractError {
 
     #[error("Duplicate initial balance addresses")]
     DuplicateInitialBalanceAddresses {},
+
+    #[error("Duplicate recipient addresses in batch")]
+    DuplicateRecipients {},
+
+    #[error("Contract is paused")]
+    Paused {},
+
+    #[error("Transfers are restricted to allowlisted addresses until trading opens")]
+    TransferRestricted {},
+
+    #[error("Trading is already open")]
+    TradingAlreadyOpen {},
+
+    #[error("Amount exceeds the maximum of {max} per transaction")]
+    MaxTxAmountExceeded { max: Uint128 },
+
+    #[error("Balance would exceed the maximum of {max} per wallet")]
+    MaxWalletBalanceExceeded { max: Uint128 },
+
+    #[error("Launch limits can only be relaxed once trading is open")]
+    LimitsTightened {},
+
+    #[error("Account {address} is frozen")]
+    AccountFrozen { address: String },
+
+    #[error("Checkpoints are only used with the Selected snapshot strategy")]
+    CheckpointNotSelected {},
+
+    #[error("No retention window is configured, history is kept forever")]
+    PruningDisabled {},
+
+    #[error("History from height {retained_from} on is still retained")]
+    PruneWithinRetention { retained_from: u64 },
+
+    #[error("Transfer fee rate cannot exceed 10000 basis points")]
+    InvalidFeeRate {},
+
+    #[error("Semver parsing error: {0}")]
+    SemVer(String),
+
+    #[error("Cannot migrate from different contract type: {previous_contract}")]
+    CannotMigrate {
+        previous_contract: String,
+    },
+
+    #[error("Cannot migrate from unsupported version: {previous_version}")]
+    CannotMigrateVersion {
+        previous_version: String,
+    },
+}
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        Self::SemVer(err.to_string())
+    }
 }
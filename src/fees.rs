@@ -0,0 +1,269 @@
+use cosmwasm_std::{
+    Addr,
+    Api,
+    Deps,
+    DepsMut,
+    Empty,
+    Env,
+    MessageInfo,
+    Response,
+    StdResult,
+    Storage,
+    Uint128,
+};
+
+use crate::error::ContractError;
+use crate::msg::{ TransferFeeInfo, TransferFeeResponse };
+use crate::roles::assert_role;
+use crate::state::{ FeeConfig, Role, FEE_CONFIG, FEE_EXEMPT };
+
+const BPS_DENOMINATOR: u128 = 10_000;
+
+/// Validates a fee schedule and turns it into storable config, without an admin.
+pub fn validate_transfer_fee(api: &dyn Api, fee: TransferFeeInfo) -> Result<FeeConfig, ContractError> {
+    if u128::from(fee.rate_bps) > BPS_DENOMINATOR {
+        return Err(ContractError::InvalidFeeRate {});
+    }
+    Ok(FeeConfig {
+        admin: None,
+        rate_bps: fee.rate_bps,
+        max_fee: fee.max_fee,
+        collector: fee.collector.map(|addr| api.addr_validate(&addr)).transpose()?,
+    })
+}
+
+/// Returns the fee owed on moving `amount` from `from` to `to`, together with the address
+/// collecting it. A None collector means the fee is burned.
+pub fn transfer_fee(
+    storage: &dyn Storage,
+    from: &Addr,
+    to: &Addr,
+    amount: Uint128
+) -> StdResult<(Uint128, Option<Addr>)> {
+    let config = FEE_CONFIG.may_load(storage)?.unwrap_or_default();
+    if config.rate_bps == 0 || FEE_EXEMPT.has(storage, from) || FEE_EXEMPT.has(storage, to) {
+        return Ok((Uint128::zero(), None));
+    }
+
+    let mut fee = amount.multiply_ratio(config.rate_bps, BPS_DENOMINATOR);
+    if let Some(max_fee) = config.max_fee {
+        fee = fee.min(max_fee);
+    }
+    Ok((fee, config.collector))
+}
+
+fn load_for_fee_admin(
+    storage: &dyn Storage,
+    info: &MessageInfo
+) -> Result<FeeConfig, ContractError> {
+    assert_role(storage, Role::FeeAdmin, &info.sender)?;
+    Ok(FEE_CONFIG.may_load(storage)?.unwrap_or_default())
+}
+
+pub fn execute_update_transfer_fee(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    fee: TransferFeeInfo
+) -> Result<Response, ContractError> {
+    let config = load_for_fee_admin(deps.storage, &info)?;
+    let config = FeeConfig {
+        admin: config.admin,
+        ..validate_transfer_fee(deps.api, fee)?
+    };
+    FEE_CONFIG.save(deps.storage, &config)?;
+
+    let res = Response::new()
+        .add_attribute("action", "update_transfer_fee")
+        .add_attribute("rate_bps", config.rate_bps.to_string())
+        .add_attribute(
+            "max_fee",
+            config.max_fee.map(|fee| fee.to_string()).unwrap_or_else(|| "None".to_string())
+        )
+        .add_attribute(
+            "collector",
+            config.collector.map(Into::into).unwrap_or_else(|| "None".to_string())
+        );
+    Ok(res)
+}
+
+pub fn execute_update_fee_exemptions(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>
+) -> Result<Response, ContractError> {
+    load_for_fee_admin(deps.storage, &info)?;
+
+    for address in &add {
+        FEE_EXEMPT.save(deps.storage, &deps.api.addr_validate(address)?, &Empty {})?;
+    }
+    for address in &remove {
+        FEE_EXEMPT.remove(deps.storage, &deps.api.addr_validate(address)?);
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "update_fee_exemptions")
+        .add_attribute("added", add.join(","))
+        .add_attribute("removed", remove.join(","));
+    Ok(res)
+}
+
+pub fn query_transfer_fee(deps: Deps) -> StdResult<TransferFeeResponse> {
+    let config = FEE_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    Ok(TransferFeeResponse {
+        admin: config.admin.map(Into::into),
+        rate_bps: config.rate_bps,
+        max_fee: config.max_fee,
+        collector: config.collector.map(Into::into),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::{ mock_dependencies, mock_env, mock_info };
+    use cosmwasm_std::{ attr, from_binary, Binary, CosmosMsg, SubMsg, WasmMsg };
+    use cw20::{ Cw20Coin, Cw20ReceiveMsg };
+
+    use crate::contract::{ execute, instantiate, query, query_balance, query_token_info };
+    use crate::msg::{ ExecuteMsg, InstantiateMsg, QueryMsg };
+
+    fn get_balance(deps: Deps, address: &str) -> Uint128 {
+        query_balance(deps, address.into()).unwrap().balance
+    }
+
+    fn do_instantiate(deps: DepsMut, fee: TransferFeeInfo) {
+        let instantiate_msg = InstantiateMsg {
+            name: "Auto Gen".to_string(),
+            symbol: "AUTO".to_string(),
+            decimals: 3,
+            initial_balances: vec![Cw20Coin {
+                address: "owner".into(),
+                amount: Uint128::new(100_000),
+            }],
+            transfer_fee: Some(fee),
+            fee_admin: Some("admin".into()),
+            fee_exemptions: Some(vec!["pair".into()]),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[]);
+        instantiate(deps, mock_env(), info, instantiate_msg).unwrap();
+    }
+
+    #[test]
+    fn transfer_pays_capped_fee_to_collector() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), TransferFeeInfo {
+            rate_bps: 100,
+            max_fee: Some(Uint128::new(500)),
+            collector: Some("treasury".into()),
+        });
+
+        let msg = ExecuteMsg::Transfer { recipient: "bob".into(), amount: Uint128::new(10_000) };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        assert_eq!(res.attributes[4], attr("fee_amount", "100"));
+        assert_eq!(get_balance(deps.as_ref(), "bob"), Uint128::new(9_900));
+        assert_eq!(get_balance(deps.as_ref(), "treasury"), Uint128::new(100));
+
+        // the cap kicks in for large transfers
+        let msg = ExecuteMsg::Transfer { recipient: "bob".into(), amount: Uint128::new(80_000) };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        assert_eq!(get_balance(deps.as_ref(), "bob"), Uint128::new(89_400));
+        assert_eq!(get_balance(deps.as_ref(), "treasury"), Uint128::new(600));
+        assert_eq!(get_balance(deps.as_ref(), "owner"), Uint128::new(10_000));
+        assert_eq!(query_token_info(deps.as_ref()).unwrap().total_supply, Uint128::new(100_000));
+    }
+
+    #[test]
+    fn burned_fee_reduces_supply_and_exempt_pairs_pay_nothing() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), TransferFeeInfo {
+            rate_bps: 1_000,
+            max_fee: None,
+            collector: None,
+        });
+
+        let env = mock_env();
+        let msg = ExecuteMsg::Send {
+            contract: "contract".into(),
+            amount: Uint128::new(1_000),
+            msg: Binary::from(br#"{"some":123}"#),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+        assert_eq!(res.attributes[4], attr("fee_amount", "100"));
+
+        // the receiving contract is told about what it actually got
+        let binary_msg = (Cw20ReceiveMsg {
+            sender: "owner".into(),
+            amount: Uint128::new(900),
+            msg: Binary::from(br#"{"some":123}"#),
+        }).into_binary().unwrap();
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: "contract".into(),
+                    msg: binary_msg,
+                    funds: vec![],
+                })
+            )
+        );
+
+        let supply: Uint128 = from_binary(
+            &query(deps.as_ref(), env.clone(), QueryMsg::TotalSupplyAt {
+                block: env.block.height,
+            }).unwrap()
+        ).unwrap();
+        assert_eq!(supply, Uint128::new(99_900));
+
+        let msg = ExecuteMsg::Transfer { recipient: "pair".into(), amount: Uint128::new(1_000) };
+        let res = execute(deps.as_mut(), env, mock_info("owner", &[]), msg).unwrap();
+        assert_eq!(res.attributes[4], attr("fee_amount", "0"));
+        assert_eq!(get_balance(deps.as_ref(), "pair"), Uint128::new(1_000));
+    }
+
+    #[test]
+    fn only_fee_admin_updates_schedule() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), TransferFeeInfo {
+            rate_bps: 100,
+            max_fee: None,
+            collector: None,
+        });
+
+        let msg = ExecuteMsg::UpdateTransferFee {
+            fee: TransferFeeInfo {
+                rate_bps: 10_001,
+                max_fee: None,
+                collector: None,
+            },
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg.clone());
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        let err = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg);
+        assert_eq!(err.unwrap_err(), ContractError::InvalidFeeRate {});
+
+        let msg = ExecuteMsg::UpdateTransferFee {
+            fee: TransferFeeInfo {
+                rate_bps: 0,
+                max_fee: None,
+                collector: None,
+            },
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        let config = query_transfer_fee(deps.as_ref()).unwrap();
+        assert_eq!(config.admin, Some("admin".to_string()));
+        assert_eq!(config.rate_bps, 0);
+
+        let msg = ExecuteMsg::UpdateFeeExemptions {
+            add: vec!["router".into()],
+            remove: vec!["pair".into()],
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), msg).unwrap();
+        assert!(FEE_EXEMPT.has(deps.as_ref().storage, &Addr::unchecked("router")));
+        assert!(!FEE_EXEMPT.has(deps.as_ref().storage, &Addr::unchecked("pair")));
+    }
+}
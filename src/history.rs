@@ -0,0 +1,325 @@
+use cosmwasm_std::{
+    attr,
+    Addr,
+    Deps,
+    DepsMut,
+    Env,
+    MessageInfo,
+    Order,
+    Response,
+    StdError,
+    StdResult,
+    Storage,
+    Uint128,
+};
+use cw_storage_plus::{ Bound, SnapshotMap };
+
+use crate::error::ContractError;
+use crate::msg::BalanceHistoryResponse;
+use crate::roles::assert_role;
+use crate::state::{
+    balances_snapshot,
+    Role,
+    SnapshotStrategy,
+    BALANCES,
+    BALANCES_PRUNED_BEFORE,
+    BALANCE_PRUNE_CURSOR,
+    HISTORY_CONFIG,
+    SUPPLY_HISTORY,
+};
+
+// settings for paging through the balance changelog
+const DEFAULT_PRUNE_LIMIT: u32 = 100;
+const MAX_PRUNE_LIMIT: u32 = 500;
+
+/// Balances recording their history with the strategy chosen at instantiation.
+pub fn balances(storage: &dyn Storage) -> StdResult<SnapshotMap<'static, &'static Addr, Uint128>> {
+    let config = HISTORY_CONFIG.may_load(storage)?.unwrap_or_default();
+    Ok(balances_snapshot(config.balance_strategy.into()))
+}
+
+/// The balance of `address` at the start of block `height`. Fails for heights that were pruned
+/// or, depending on the strategy, never recorded.
+pub fn balance_at(storage: &dyn Storage, address: &Addr, height: u64) -> StdResult<Option<Uint128>> {
+    let pruned_before = BALANCES_PRUNED_BEFORE.may_load(storage)?.unwrap_or_default();
+    if height < pruned_before {
+        return Err(
+            StdError::generic_err(
+                format!("Balance history before height {} has been pruned", pruned_before)
+            )
+        );
+    }
+    balances(storage)?.may_load_at_height(storage, address, height)
+}
+
+pub fn execute_checkpoint(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, Role::Checkpointer, &info.sender)?;
+    let config = HISTORY_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    if config.balance_strategy != SnapshotStrategy::Selected {
+        return Err(ContractError::CheckpointNotSelected {});
+    }
+    BALANCES.add_checkpoint(deps.storage, env.block.height)?;
+
+    let res = Response::new()
+        .add_attribute("action", "checkpoint")
+        .add_attribute("height", env.block.height.to_string());
+    Ok(res)
+}
+
+/// Fails unless history before `before_height` is outside the retention window.
+fn assert_prunable(storage: &dyn Storage, env: &Env, before_height: u64) -> Result<(), ContractError> {
+    let config = HISTORY_CONFIG.may_load(storage)?.unwrap_or_default();
+    let retention = config.retention.ok_or(ContractError::PruningDisabled {})?;
+    let retained_from = env.block.height.saturating_sub(retention);
+    if before_height > retained_from {
+        return Err(ContractError::PruneWithinRetention { retained_from });
+    }
+    Ok(())
+}
+
+pub fn execute_prune_balance_history(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    before_height: u64,
+    limit: Option<u32>
+) -> Result<Response, ContractError> {
+    assert_prunable(deps.storage, &env, before_height)?;
+    // queries are refused right away, even though a pass may take several calls
+    let pruned_before = BALANCES_PRUNED_BEFORE.may_load(deps.storage)?
+        .unwrap_or_default()
+        .max(before_height);
+    BALANCES_PRUNED_BEFORE.save(deps.storage, &pruned_before)?;
+
+    let limit = limit.unwrap_or(DEFAULT_PRUNE_LIMIT).min(MAX_PRUNE_LIMIT) as usize;
+    let cursor = BALANCE_PRUNE_CURSOR.may_load(deps.storage)?;
+    let start = cursor.as_ref().map(|(address, height)| Bound::exclusive((address, *height)));
+    let entries = BALANCES.changelog()
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut pruned = 0u32;
+    for (address, height) in &entries {
+        if *height < pruned_before {
+            BALANCES.changelog().remove(deps.storage, (address, *height));
+            pruned += 1;
+        }
+    }
+    // a short page means the whole changelog was scanned, so the next pass starts over
+    let done = entries.len() < limit;
+    match entries.last() {
+        Some(last) if !done => BALANCE_PRUNE_CURSOR.save(deps.storage, last)?,
+        _ => BALANCE_PRUNE_CURSOR.remove(deps.storage),
+    }
+
+    let res = Response::new().add_attributes(
+        vec![
+            attr("action", "prune_balance_history"),
+            attr("pruned_before", pruned_before.to_string()),
+            attr("pruned", pruned.to_string()),
+            attr("done", done.to_string())
+        ]
+    );
+    Ok(res)
+}
+
+pub fn execute_prune_supply_history(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    before_height: u64,
+    limit: Option<u32>
+) -> Result<Response, ContractError> {
+    assert_prunable(deps.storage, &env, before_height)?;
+    let limit = limit.unwrap_or(DEFAULT_PRUNE_LIMIT).min(MAX_PRUNE_LIMIT) as usize;
+    let pruned = SUPPLY_HISTORY.prune(deps.storage, before_height, limit)?;
+
+    let res = Response::new().add_attributes(
+        vec![
+            attr("action", "prune_supply_history"),
+            attr("pruned_before", SUPPLY_HISTORY.pruned_before(deps.storage)?.to_string()),
+            attr("pruned", pruned.to_string()),
+            attr("done", (pruned < limit).to_string())
+        ]
+    );
+    Ok(res)
+}
+
+pub fn query_balance_history(deps: Deps) -> StdResult<BalanceHistoryResponse> {
+    let config = HISTORY_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    Ok(BalanceHistoryResponse {
+        strategy: config.balance_strategy,
+        retention: config.retention,
+        pruned_before: BALANCES_PRUNED_BEFORE.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::{ mock_dependencies, mock_env, mock_info };
+    use cw20::{ Cw20Coin, MinterResponse };
+
+    use crate::contract::{ execute, instantiate, query_balance_at };
+    use crate::enumerable::query_supply_history;
+    use crate::msg::{ ExecuteMsg, InstantiateMsg, OrderBy, SupplyHistoryEntry };
+
+    fn do_instantiate(deps: DepsMut, strategy: SnapshotStrategy, retention: Option<u64>) {
+        let instantiate_msg = InstantiateMsg {
+            name: "Auto Gen".to_string(),
+            symbol: "AUTO".to_string(),
+            decimals: 3,
+            initial_balances: vec![Cw20Coin {
+                address: "owner".into(),
+                amount: Uint128::new(1000),
+            }],
+            mint: Some(MinterResponse {
+                minter: "minter".into(),
+                cap: None,
+            }),
+            owner: Some("owner".into()),
+            balance_snapshot_strategy: Some(strategy),
+            history_retention: retention,
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[]);
+        instantiate(deps, mock_env(), info, instantiate_msg).unwrap();
+    }
+
+    fn transfer(amount: u128) -> ExecuteMsg {
+        ExecuteMsg::Transfer { recipient: "bob".into(), amount: Uint128::new(amount) }
+    }
+
+    #[test]
+    fn selected_strategy_records_checkpointed_heights() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), SnapshotStrategy::Selected, None);
+        let mut env = mock_env();
+        let owner = mock_info("owner", &[]);
+
+        env.block.height += 1;
+        let checkpoint = ExecuteMsg::Checkpoint {};
+        let err = execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), checkpoint.clone());
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        let grant = ExecuteMsg::GrantRole { role: Role::Checkpointer, address: "bot".into() };
+        execute(deps.as_mut(), env.clone(), owner.clone(), grant).unwrap();
+        execute(deps.as_mut(), env.clone(), mock_info("bot", &[]), checkpoint).unwrap();
+        let checkpointed = env.block.height;
+
+        env.block.height += 1;
+        execute(deps.as_mut(), env.clone(), owner.clone(), transfer(100)).unwrap();
+        env.block.height += 1;
+        execute(deps.as_mut(), env.clone(), owner, transfer(100)).unwrap();
+
+        let balance = query_balance_at(deps.as_ref(), "owner".into(), checkpointed).unwrap().balance;
+        assert_eq!(balance, Uint128::new(1000));
+        query_balance_at(deps.as_ref(), "owner".into(), checkpointed + 1).unwrap_err();
+
+        // without a retention window nothing can be pruned
+        let msg = ExecuteMsg::PruneBalanceHistory { before_height: 1, limit: None };
+        let err = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::PruningDisabled {});
+    }
+
+    #[test]
+    fn pruning_keeps_the_retention_window() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), SnapshotStrategy::EveryBlock, Some(100));
+        let mut env = mock_env();
+        let start = env.block.height;
+        let owner = mock_info("owner", &[]);
+
+        for _ in 0..3 {
+            env.block.height += 10;
+            execute(deps.as_mut(), env.clone(), owner.clone(), transfer(100)).unwrap();
+        }
+        let err = execute(deps.as_mut(), env.clone(), owner, ExecuteMsg::Checkpoint {});
+        assert_eq!(err.unwrap_err(), ContractError::CheckpointNotSelected {});
+
+        env.block.height = start + 120;
+        let msg = ExecuteMsg::PruneBalanceHistory { before_height: start + 25, limit: Some(2) };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg.clone());
+        assert_eq!(err.unwrap_err(), ContractError::PruneWithinRetention { retained_from: start + 20 });
+
+        // the changelog holds the initial balance and three changes for owner, three changes for
+        // bob, and five of those entries are before start + 25
+        env.block.height = start + 125;
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg.clone()).unwrap();
+        assert!(res.attributes.contains(&attr("done", "false")));
+        let mut pruned = 2;
+        while pruned < 5 {
+            let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg.clone());
+            let res = res.unwrap();
+            let count = res.attributes.iter().find(|a| a.key == "pruned").unwrap();
+            pruned += count.value.parse::<u32>().unwrap();
+        }
+        assert_eq!(BALANCES.changelog().keys(&deps.storage, None, None, Order::Ascending).count(), 2);
+
+        let balance = query_balance_at(deps.as_ref(), "owner".into(), start + 25).unwrap().balance;
+        assert_eq!(balance, Uint128::new(800));
+        let balance = query_balance_at(deps.as_ref(), "bob".into(), start + 30).unwrap().balance;
+        assert_eq!(balance, Uint128::new(200));
+        let err = query_balance_at(deps.as_ref(), "owner".into(), start + 15).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err(format!("Balance history before height {} has been pruned", start + 25))
+        );
+        assert_eq!(query_balance_history(deps.as_ref()).unwrap().pruned_before, start + 25);
+    }
+
+    #[test]
+    fn supply_history_records_mints_and_burns() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), SnapshotStrategy::EveryBlock, Some(100));
+        let mut env = mock_env();
+        let start = env.block.height;
+
+        env.block.height += 1;
+        let msg = ExecuteMsg::Mint { recipient: "owner".into(), amount: Uint128::new(500) };
+        execute(deps.as_mut(), env.clone(), mock_info("minter", &[]), msg).unwrap();
+        let msg = ExecuteMsg::Burn { amount: Uint128::new(200) };
+        execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+        env.block.height += 1;
+        let msg = ExecuteMsg::Burn { amount: Uint128::new(300) };
+        execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+        let entry = |height: u64, total_supply: u128, minted: u128, burned: u128| {
+            SupplyHistoryEntry {
+                height,
+                total_supply: Uint128::new(total_supply),
+                minted: Uint128::new(minted),
+                burned: Uint128::new(burned),
+            }
+        };
+        let res = query_supply_history(deps.as_ref(), None, None, None, None).unwrap();
+        assert_eq!(res.entries, vec![
+            entry(start, 1000, 1000, 0),
+            entry(start + 1, 1300, 500, 200),
+            entry(start + 2, 1000, 0, 300)
+        ]);
+        let order = Some(OrderBy::Descending);
+        let res = query_supply_history(deps.as_ref(), None, Some(start + 2), Some(1), order);
+        assert_eq!(res.unwrap().entries, vec![entry(start + 1, 1300, 500, 200)]);
+
+        // the last record before the cutoff is kept, as it holds the supply after it
+        env.block.height = start + 110;
+        let msg = ExecuteMsg::PruneSupplyHistory { before_height: start + 5, limit: None };
+        let res = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap();
+        assert!(res.attributes.contains(&attr("pruned", "2")));
+        assert_eq!(SUPPLY_HISTORY.at(&deps.storage, start + 5).unwrap(), Uint128::new(1000));
+        let err = SUPPLY_HISTORY.at(&deps.storage, start + 1).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err(format!("Supply history before height {} has been pruned", start + 5))
+        );
+        let res = query_supply_history(deps.as_ref(), None, None, None, None).unwrap();
+        assert_eq!(res.entries, vec![entry(start + 2, 1000, 0, 300)]);
+        assert_eq!(res.pruned_before, start + 5);
+    }
+}
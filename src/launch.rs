@@ -0,0 +1,402 @@
+use cosmwasm_std::{
+    Addr,
+    Deps,
+    DepsMut,
+    Empty,
+    Env,
+    MessageInfo,
+    Response,
+    StdResult,
+    Storage,
+    Uint128,
+};
+
+use crate::error::ContractError;
+use crate::msg::{ LaunchLimitsResponse, TransferRestrictionResponse };
+use crate::roles::assert_role;
+use crate::state::{
+    LaunchLimits,
+    Role,
+    TransferRestriction,
+    LAUNCH_LIMITS,
+    LIMIT_EXEMPT,
+    TRANSFER_ALLOWLIST,
+    TRANSFER_RESTRICTION,
+};
+
+/// Before trading opens, fails with `ContractError::TransferRestricted` unless both sides of
+/// a transfer are allowlisted.
+pub fn assert_transfer_allowed(
+    storage: &dyn Storage,
+    from: &Addr,
+    to: &Addr
+) -> Result<(), ContractError> {
+    let restriction = TRANSFER_RESTRICTION.may_load(storage)?.unwrap_or_default();
+    if
+        restriction.allowlist_only &&
+        !(TRANSFER_ALLOWLIST.has(storage, from) && TRANSFER_ALLOWLIST.has(storage, to))
+    {
+        return Err(ContractError::TransferRestricted {});
+    }
+    Ok(())
+}
+
+impl LaunchLimits {
+    /// Fails if moving `amount` in one go breaks the transaction limit.
+    pub fn check_tx_amount(&self, amount: Uint128) -> Result<(), ContractError> {
+        if let Some(max) = self.max_tx_amount {
+            if amount > max {
+                return Err(ContractError::MaxTxAmountExceeded { max });
+            }
+        }
+        Ok(())
+    }
+
+    /// Fails if crediting `amount` to an account, bringing its balance to `balance`, breaks
+    /// one of the limits.
+    pub fn check(&self, amount: Uint128, balance: Uint128) -> Result<(), ContractError> {
+        self.check_tx_amount(amount)?;
+        if let Some(max) = self.max_wallet_balance {
+            if balance > max {
+                return Err(ContractError::MaxWalletBalanceExceeded { max });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the launch limits that apply to `address`, which are none if it is exempt.
+pub fn limits_for(storage: &dyn Storage, address: &Addr) -> StdResult<LaunchLimits> {
+    if LIMIT_EXEMPT.has(storage, address) {
+        return Ok(LaunchLimits::default());
+    }
+    Ok(LAUNCH_LIMITS.may_load(storage)?.unwrap_or_default())
+}
+
+/// A limit is relaxed when it is removed or raised.
+fn is_relaxed(old: Option<Uint128>, new: Option<Uint128>) -> bool {
+    new.is_none() || matches!((old, new), (Some(old), Some(new)) if new >= old)
+}
+
+fn load_for_launch_admin(
+    storage: &dyn Storage,
+    info: &MessageInfo
+) -> Result<TransferRestriction, ContractError> {
+    assert_role(storage, Role::LaunchAdmin, &info.sender)?;
+    Ok(TRANSFER_RESTRICTION.may_load(storage)?.unwrap_or_default())
+}
+
+pub fn execute_update_transfer_allowlist(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>
+) -> Result<Response, ContractError> {
+    let restriction = load_for_launch_admin(deps.storage, &info)?;
+    if !restriction.allowlist_only {
+        return Err(ContractError::TradingAlreadyOpen {});
+    }
+
+    for address in &add {
+        TRANSFER_ALLOWLIST.save(deps.storage, &deps.api.addr_validate(address)?, &Empty {})?;
+    }
+    for address in &remove {
+        TRANSFER_ALLOWLIST.remove(deps.storage, &deps.api.addr_validate(address)?);
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "update_transfer_allowlist")
+        .add_attribute("added", add.join(","))
+        .add_attribute("removed", remove.join(","));
+    Ok(res)
+}
+
+pub fn execute_open_trading(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo
+) -> Result<Response, ContractError> {
+    let mut restriction = load_for_launch_admin(deps.storage, &info)?;
+    if !restriction.allowlist_only {
+        return Err(ContractError::TradingAlreadyOpen {});
+    }
+    restriction.allowlist_only = false;
+    TRANSFER_RESTRICTION.save(deps.storage, &restriction)?;
+
+    Ok(Response::new().add_attribute("action", "open_trading"))
+}
+
+pub fn execute_update_launch_limits(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    max_tx_amount: Option<Uint128>,
+    max_wallet_balance: Option<Uint128>
+) -> Result<Response, ContractError> {
+    let restriction = load_for_launch_admin(deps.storage, &info)?;
+    let limits = LAUNCH_LIMITS.may_load(deps.storage)?.unwrap_or_default();
+    let relaxed =
+        is_relaxed(limits.max_tx_amount, max_tx_amount) &&
+        is_relaxed(limits.max_wallet_balance, max_wallet_balance);
+    if !(restriction.allowlist_only || relaxed) {
+        return Err(ContractError::LimitsTightened {});
+    }
+    LAUNCH_LIMITS.save(deps.storage, &LaunchLimits { max_tx_amount, max_wallet_balance })?;
+
+    let res = Response::new()
+        .add_attribute("action", "update_launch_limits")
+        .add_attribute(
+            "max_tx_amount",
+            max_tx_amount.map(|max| max.to_string()).unwrap_or_else(|| "None".to_string())
+        )
+        .add_attribute(
+            "max_wallet_balance",
+            max_wallet_balance.map(|max| max.to_string()).unwrap_or_else(|| "None".to_string())
+        );
+    Ok(res)
+}
+
+pub fn execute_update_limit_exemptions(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>
+) -> Result<Response, ContractError> {
+    let restriction = load_for_launch_admin(deps.storage, &info)?;
+    // taking an exemption away tightens the limits for that address
+    if !restriction.allowlist_only && !remove.is_empty() {
+        return Err(ContractError::LimitsTightened {});
+    }
+
+    for address in &add {
+        LIMIT_EXEMPT.save(deps.storage, &deps.api.addr_validate(address)?, &Empty {})?;
+    }
+    for address in &remove {
+        LIMIT_EXEMPT.remove(deps.storage, &deps.api.addr_validate(address)?);
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "update_limit_exemptions")
+        .add_attribute("added", add.join(","))
+        .add_attribute("removed", remove.join(","));
+    Ok(res)
+}
+
+pub fn query_launch_limits(deps: Deps) -> StdResult<LaunchLimitsResponse> {
+    let limits = LAUNCH_LIMITS.may_load(deps.storage)?.unwrap_or_default();
+    Ok(LaunchLimitsResponse {
+        max_tx_amount: limits.max_tx_amount,
+        max_wallet_balance: limits.max_wallet_balance,
+    })
+}
+
+pub fn query_transfer_restriction(deps: Deps) -> StdResult<TransferRestrictionResponse> {
+    let restriction = TRANSFER_RESTRICTION.may_load(deps.storage)?.unwrap_or_default();
+    Ok(TransferRestrictionResponse {
+        admin: restriction.admin.map(Into::into),
+        allowlist_only: restriction.allowlist_only,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::{ mock_dependencies, mock_env, mock_info };
+    use cosmwasm_std::{ Binary, Uint128 };
+    use cw20::Cw20Coin;
+
+    use crate::contract::{ execute, instantiate, query_balance };
+    use crate::enumerable::query_transfer_allowlist;
+    use crate::msg::{ ExecuteMsg, InstantiateMsg, TransferFeeInfo };
+
+    fn do_instantiate(deps: DepsMut) {
+        let instantiate_msg = InstantiateMsg {
+            name: "Auto Gen".to_string(),
+            symbol: "AUTO".to_string(),
+            decimals: 3,
+            initial_balances: vec![Cw20Coin {
+                address: "team".into(),
+                amount: Uint128::new(1000),
+            }],
+            launch_admin: Some("admin".into()),
+            allowlist_only: Some(true),
+            transfer_allowlist: Some(vec!["team".into()]),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[]);
+        instantiate(deps, mock_env(), info, instantiate_msg).unwrap();
+    }
+
+    #[test]
+    fn only_allowlisted_addresses_trade_before_launch() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+        let team = mock_info("team", &[]);
+
+        let msg = ExecuteMsg::Send {
+            contract: "lp_seeder".into(),
+            amount: Uint128::new(100),
+            msg: Binary::default(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), team.clone(), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::TransferRestricted {});
+
+        let allowlist = ExecuteMsg::UpdateTransferAllowlist {
+            add: vec!["lp_seeder".into()],
+            remove: vec![],
+        };
+        let err = execute(deps.as_mut(), mock_env(), team.clone(), allowlist.clone());
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), allowlist.clone()).unwrap();
+        execute(deps.as_mut(), mock_env(), team.clone(), msg).unwrap();
+        let res = query_transfer_allowlist(deps.as_ref(), None, None).unwrap();
+        assert_eq!(res.addresses, vec!["lp_seeder".to_string(), "team".to_string()]);
+
+        // allowances cannot be used to get around the allowlist either
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: "spender".into(),
+            amount: Uint128::new(100),
+            expires: None,
+        };
+        execute(deps.as_mut(), mock_env(), team.clone(), msg).unwrap();
+        let msg = ExecuteMsg::TransferFrom {
+            owner: "team".into(),
+            recipient: "sniper".into(),
+            amount: Uint128::new(100),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("spender", &[]), msg.clone());
+        assert_eq!(err.unwrap_err(), ContractError::TransferRestricted {});
+
+        let admin = mock_info("admin", &[]);
+        execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::OpenTrading {}).unwrap();
+        assert!(!query_transfer_restriction(deps.as_ref()).unwrap().allowlist_only);
+        execute(deps.as_mut(), mock_env(), mock_info("spender", &[]), msg).unwrap();
+        assert_eq!(query_balance(deps.as_ref(), "sniper".into()).unwrap().balance, Uint128::new(100));
+
+        // opening is final
+        let err = execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::OpenTrading {});
+        assert_eq!(err.unwrap_err(), ContractError::TradingAlreadyOpen {});
+        let err = execute(deps.as_mut(), mock_env(), admin, allowlist);
+        assert_eq!(err.unwrap_err(), ContractError::TradingAlreadyOpen {});
+    }
+
+    #[test]
+    fn launch_limits_cap_what_an_account_receives() {
+        let mut deps = mock_dependencies();
+        let instantiate_msg = InstantiateMsg {
+            name: "Auto Gen".to_string(),
+            symbol: "AUTO".to_string(),
+            decimals: 3,
+            initial_balances: vec![Cw20Coin {
+                address: "team".into(),
+                amount: Uint128::new(1000),
+            }],
+            launch_admin: Some("admin".into()),
+            max_tx_amount: Some(Uint128::new(100)),
+            max_wallet_balance: Some(Uint128::new(150)),
+            limit_exemptions: Some(vec!["pair".into()]),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), instantiate_msg).unwrap();
+        let team = mock_info("team", &[]);
+        let transfer = |recipient: &str, amount: u128| ExecuteMsg::Transfer {
+            recipient: recipient.into(),
+            amount: Uint128::new(amount),
+        };
+
+        let err = execute(deps.as_mut(), mock_env(), team.clone(), transfer("whale", 101));
+        assert_eq!(err.unwrap_err(), ContractError::MaxTxAmountExceeded { max: Uint128::new(100) });
+        execute(deps.as_mut(), mock_env(), team.clone(), transfer("whale", 100)).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), team.clone(), transfer("whale", 51));
+        assert_eq!(err.unwrap_err(), ContractError::MaxWalletBalanceExceeded {
+            max: Uint128::new(150),
+        });
+        // exempt addresses can receive any amount
+        execute(deps.as_mut(), mock_env(), team.clone(), transfer("pair", 500)).unwrap();
+
+        // trading is open from the start here, so limits can only be relaxed
+        let admin = mock_info("admin", &[]);
+        let tighten = ExecuteMsg::UpdateLaunchLimits {
+            max_tx_amount: Some(Uint128::new(50)),
+            max_wallet_balance: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), admin.clone(), tighten).unwrap_err();
+        assert_eq!(err, ContractError::LimitsTightened {});
+        let msg = ExecuteMsg::UpdateLimitExemptions { add: vec![], remove: vec!["pair".into()] };
+        let err = execute(deps.as_mut(), mock_env(), admin.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::LimitsTightened {});
+
+        let relax = ExecuteMsg::UpdateLaunchLimits {
+            max_tx_amount: Some(Uint128::new(200)),
+            max_wallet_balance: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), team.clone(), relax.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), admin, relax).unwrap();
+        execute(deps.as_mut(), mock_env(), team, transfer("whale", 200)).unwrap();
+        assert_eq!(query_balance(deps.as_ref(), "whale".into()).unwrap().balance, Uint128::new(300));
+        assert_eq!(query_launch_limits(deps.as_ref()).unwrap(), LaunchLimitsResponse {
+            max_tx_amount: Some(Uint128::new(200)),
+            max_wallet_balance: None,
+        });
+    }
+
+    #[test]
+    fn fee_collector_is_not_held_to_launch_limits() {
+        let mut deps = mock_dependencies();
+        let instantiate_msg = InstantiateMsg {
+            name: "Auto Gen".to_string(),
+            symbol: "AUTO".to_string(),
+            decimals: 3,
+            initial_balances: vec![
+                Cw20Coin { address: "team".into(), amount: Uint128::new(1000) },
+                Cw20Coin { address: "treasury".into(), amount: Uint128::new(150) }
+            ],
+            transfer_fee: Some(TransferFeeInfo {
+                rate_bps: 1_000,
+                max_fee: None,
+                collector: Some("treasury".into()),
+            }),
+            max_wallet_balance: Some(Uint128::new(150)),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), instantiate_msg).unwrap();
+
+        let msg = ExecuteMsg::Transfer { recipient: "bob".into(), amount: Uint128::new(100) };
+        execute(deps.as_mut(), mock_env(), mock_info("team", &[]), msg).unwrap();
+        assert_eq!(query_balance(deps.as_ref(), "bob".into()).unwrap().balance, Uint128::new(90));
+        let balance = query_balance(deps.as_ref(), "treasury".into()).unwrap().balance;
+        assert_eq!(balance, Uint128::new(160));
+    }
+
+    #[test]
+    fn transaction_limit_covers_the_fee() {
+        let mut deps = mock_dependencies();
+        let instantiate_msg = InstantiateMsg {
+            name: "Auto Gen".to_string(),
+            symbol: "AUTO".to_string(),
+            decimals: 3,
+            initial_balances: vec![Cw20Coin { address: "team".into(), amount: Uint128::new(1000) }],
+            transfer_fee: Some(TransferFeeInfo {
+                rate_bps: 1_000,
+                max_fee: None,
+                collector: Some("treasury".into()),
+            }),
+            max_tx_amount: Some(Uint128::new(100)),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), instantiate_msg).unwrap();
+
+        let transfer = |amount: u128| ExecuteMsg::Transfer {
+            recipient: "bob".into(),
+            amount: Uint128::new(amount),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("team", &[]), transfer(100)).unwrap();
+        // only 99 would be credited, but 110 leave the sender
+        let err = execute(deps.as_mut(), mock_env(), mock_info("team", &[]), transfer(110));
+        assert_eq!(err.unwrap_err(), ContractError::MaxTxAmountExceeded { max: Uint128::new(100) });
+    }
+}
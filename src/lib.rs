@@ -1,8 +1,22 @@
 pub mod contract;
 pub mod allowances;
+pub mod permit;
 pub mod error;
 pub mod state;
 pub mod msg;
 pub mod enumerable;
+pub mod migrations;
+pub mod history;
+pub mod ownership;
+pub mod pause;
+pub mod roles;
+pub mod timelock;
+pub mod compliance;
+pub mod launch;
+pub mod fees;
+pub mod minters;
+pub mod mint_proposals;
+pub mod vesting;
+pub mod votes;
 #[cfg(test)]
 pub mod testing;
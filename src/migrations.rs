@@ -0,0 +1,245 @@
+use cosmwasm_std::{ Addr, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage };
+use cw_storage_plus::Bound;
+
+use crate::error::ContractError;
+use crate::state::{
+    allowances,
+    MigrationStep,
+    BALANCES,
+    HOLDER_COUNT,
+    LEGACY_ALLOWANCES_SPENDER,
+    LEGACY_TOTAL_SUPPLY_HISTORY,
+    PENDING_MIGRATIONS,
+    SUPPLY_HISTORY,
+    SupplyRecord,
+};
+
+// settings for paging through migration work
+pub const DEFAULT_MIGRATION_LIMIT: u32 = 100;
+const MAX_MIGRATION_LIMIT: u32 = 500;
+
+/// Appends steps to the migration queue. Nothing is processed until `run_pending_migrations`.
+pub fn schedule_migration(storage: &mut dyn Storage, steps: Vec<MigrationStep>) -> StdResult<()> {
+    let mut pending = PENDING_MIGRATIONS.may_load(storage)?.unwrap_or_default();
+    pending.extend(steps);
+    PENDING_MIGRATIONS.save(storage, &pending)
+}
+
+/// Processes up to `limit` storage entries of queued migration work.
+/// Returns the number of steps still pending afterwards.
+pub fn run_pending_migrations(
+    storage: &mut dyn Storage,
+    height: u64,
+    limit: u32
+) -> StdResult<usize> {
+    let mut pending = PENDING_MIGRATIONS.may_load(storage)?.unwrap_or_default();
+    let mut budget = limit.min(MAX_MIGRATION_LIMIT);
+
+    while !pending.is_empty() {
+        match run_step(storage, height, pending[0].clone(), &mut budget)? {
+            Some(remaining) => {
+                pending[0] = remaining;
+                break;
+            }
+            None => {
+                pending.remove(0);
+            }
+        }
+    }
+
+    if pending.is_empty() {
+        PENDING_MIGRATIONS.remove(storage);
+    } else {
+        PENDING_MIGRATIONS.save(storage, &pending)?;
+    }
+    Ok(pending.len())
+}
+
+/// Returns true while the spender index is incomplete and must not be used for lookups.
+pub fn allowance_index_pending(storage: &dyn Storage) -> StdResult<bool> {
+    let pending = PENDING_MIGRATIONS.may_load(storage)?.unwrap_or_default();
+    Ok(pending.iter().any(|step| matches!(step, MigrationStep::AllowanceIndexBackfill { .. })))
+}
+
+/// Returns false while `address` still has to be counted by the holder count backfill, in
+/// which case balance changes must not adjust `HOLDER_COUNT` themselves.
+pub fn holder_counted(storage: &dyn Storage, address: &Addr) -> StdResult<bool> {
+    let pending = PENDING_MIGRATIONS.may_load(storage)?.unwrap_or_default();
+    let cursor = pending.iter().find_map(|step| {
+        match step {
+            MigrationStep::HolderCountBackfill { start_after } => Some(start_after),
+            _ => None,
+        }
+    });
+    Ok(match cursor {
+        None => true,
+        Some(start_after) => matches!(start_after, Some(last) if address <= last),
+    })
+}
+
+/// Returns true while the holder count is still being backfilled and is not reliable.
+pub fn holder_count_pending(storage: &dyn Storage) -> StdResult<bool> {
+    let pending = PENDING_MIGRATIONS.may_load(storage)?.unwrap_or_default();
+    Ok(pending.iter().any(|step| matches!(step, MigrationStep::HolderCountBackfill { .. })))
+}
+
+pub fn supply_history_pending(storage: &dyn Storage) -> StdResult<bool> {
+    let pending = PENDING_MIGRATIONS.may_load(storage)?.unwrap_or_default();
+    Ok(pending.iter().any(|step| matches!(step, MigrationStep::SupplyHistoryBackfill)))
+}
+
+pub fn execute_continue_migration(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    limit: Option<u32>
+) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_MIGRATION_LIMIT);
+    let remaining = run_pending_migrations(deps.storage, env.block.height, limit)?;
+
+    let res = Response::new()
+        .add_attribute("action", "continue_migration")
+        .add_attribute("remaining_steps", remaining.to_string());
+    Ok(res)
+}
+
+/// Runs a single step within the remaining budget, returning the step to resume from if it
+/// did not finish.
+fn run_step(
+    storage: &mut dyn Storage,
+    height: u64,
+    step: MigrationStep,
+    budget: &mut u32
+) -> StdResult<Option<MigrationStep>> {
+    match step {
+        MigrationStep::AllowanceIndexBackfill { start_after } =>
+            backfill_allowance_index(storage, height, start_after, budget),
+        MigrationStep::LegacyAllowanceCleanup => cleanup_legacy_allowances(storage, budget),
+        MigrationStep::HolderCountBackfill { start_after } =>
+            backfill_holder_count(storage, height, start_after, budget),
+        MigrationStep::SupplyHistoryBackfill => backfill_supply_history(storage, budget),
+    }
+}
+
+fn backfill_allowance_index(
+    storage: &mut dyn Storage,
+    height: u64,
+    start_after: Option<(Addr, Addr)>,
+    budget: &mut u32
+) -> StdResult<Option<MigrationStep>> {
+    let start = start_after.as_ref().map(|(owner, spender)| Bound::exclusive((owner, spender)));
+    let entries = allowances()
+        .range(storage, start, None, Order::Ascending)
+        .take(*budget as usize)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    // saving over the existing value writes the index entry it is missing
+    for ((owner, spender), allowance) in &entries {
+        allowances().save(storage, (owner, spender), allowance, height)?;
+    }
+
+    let processed = entries.len() as u32;
+    if processed < *budget {
+        *budget -= processed;
+        return Ok(None);
+    }
+    *budget = 0;
+    let start_after = entries.into_iter().last().map(|(key, _)| key).or(start_after);
+    Ok(Some(MigrationStep::AllowanceIndexBackfill { start_after }))
+}
+
+fn cleanup_legacy_allowances(
+    storage: &mut dyn Storage,
+    budget: &mut u32
+) -> StdResult<Option<MigrationStep>> {
+    let keys = LEGACY_ALLOWANCES_SPENDER.keys(storage, None, None, Order::Ascending)
+        .take(*budget as usize)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (spender, owner) in &keys {
+        LEGACY_ALLOWANCES_SPENDER.remove(storage, (spender, owner));
+    }
+
+    let processed = keys.len() as u32;
+    if processed < *budget {
+        *budget -= processed;
+        return Ok(None);
+    }
+    *budget = 0;
+    Ok(Some(MigrationStep::LegacyAllowanceCleanup))
+}
+
+fn backfill_holder_count(
+    storage: &mut dyn Storage,
+    height: u64,
+    start_after: Option<Addr>,
+    budget: &mut u32
+) -> StdResult<Option<MigrationStep>> {
+    // nothing is tracked before the first page, so any earlier count is stale
+    let mut count = match start_after {
+        Some(_) => HOLDER_COUNT.may_load(storage)?.unwrap_or_default(),
+        None => 0,
+    };
+    let start = start_after.as_ref().map(Bound::exclusive);
+    let entries = BALANCES.range(storage, start, None, Order::Ascending)
+        .take(*budget as usize)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    count += entries
+        .iter()
+        .filter(|(_, balance)| !balance.is_zero())
+        .count() as u64;
+    HOLDER_COUNT.save(storage, &count, height)?;
+
+    let processed = entries.len() as u32;
+    if processed < *budget {
+        *budget -= processed;
+        return Ok(None);
+    }
+    *budget = 0;
+    let start_after = entries.into_iter().last().map(|(address, _)| address).or(start_after);
+    Ok(Some(MigrationStep::HolderCountBackfill { start_after }))
+}
+
+fn backfill_supply_history(
+    storage: &mut dyn Storage,
+    budget: &mut u32
+) -> StdResult<Option<MigrationStep>> {
+    let entries = LEGACY_TOTAL_SUPPLY_HISTORY.range(storage, None, None, Order::Ascending)
+        .take(*budget as usize)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    // only totals were kept, so each block's net change counts as minted or burned
+    for (height, total_supply) in &entries {
+        let previous = SUPPLY_HISTORY.records()
+            .range(storage, None, Some(Bound::exclusive(*height)), Order::Descending)
+            .next()
+            .transpose()?
+            .map(|(_, record)| record.total_supply)
+            .unwrap_or_default();
+        let mut record = SupplyRecord {
+            total_supply: *total_supply,
+            minted: total_supply.saturating_sub(previous),
+            burned: previous.saturating_sub(*total_supply),
+        };
+        // mints and burns after the migrate call in the same block already wrote a record,
+        // which holds the later total, so only the legacy change is added to it
+        if let Some(existing) = SUPPLY_HISTORY.records().may_load(storage, *height)? {
+            record = SupplyRecord {
+                total_supply: existing.total_supply,
+                minted: existing.minted + record.minted,
+                burned: existing.burned + record.burned,
+            };
+        }
+        SUPPLY_HISTORY.records().save(storage, *height, &record)?;
+        LEGACY_TOTAL_SUPPLY_HISTORY.remove(storage, *height);
+    }
+
+    let processed = entries.len() as u32;
+    if processed < *budget {
+        *budget -= processed;
+        return Ok(None);
+    }
+    *budget = 0;
+    Ok(Some(MigrationStep::SupplyHistoryBackfill))
+}
@@ -0,0 +1,343 @@
+use cosmwasm_std::{
+    attr,
+    Deps,
+    DepsMut,
+    Env,
+    MessageInfo,
+    Order,
+    Response,
+    StdResult,
+    Storage,
+    Uint128,
+};
+
+use crate::contract::mint_tokens;
+use crate::error::ContractError;
+use crate::msg::MintProposalResponse;
+use crate::roles::{ assert_role, has_role };
+use crate::state::{
+    MintApprovalConfig,
+    MintProposal,
+    Role,
+    MINT_APPROVAL_CONFIG,
+    MINT_PROPOSALS,
+    NEXT_MINT_PROPOSAL_ID,
+    ROLES,
+};
+
+/// Fails unless the threshold can be reached by the current mint signers. Direct mints are
+/// refused while approvals are enabled, so an unreachable threshold would stop all minting.
+pub fn validate_mint_approval(
+    storage: &dyn Storage,
+    config: &MintApprovalConfig
+) -> Result<(), ContractError> {
+    let signers = ROLES.prefix(Role::MintSigner.as_str())
+        .keys(storage, None, None, Order::Ascending)
+        .count();
+    if config.threshold == 0 || (config.threshold as usize) > signers {
+        return Err(ContractError::InvalidMintThreshold {});
+    }
+    Ok(())
+}
+
+fn load_for_mint_signer(
+    storage: &dyn Storage,
+    info: &MessageInfo
+) -> Result<MintApprovalConfig, ContractError> {
+    assert_role(storage, Role::MintSigner, &info.sender)?;
+    MINT_APPROVAL_CONFIG.may_load(storage)?.ok_or(ContractError::MintApprovalDisabled {})
+}
+
+/// Mints the proposed amount once the approvals of addresses that are still mint signers reach
+/// the threshold, and saves the proposal. Returns whether the mint happened.
+fn save_or_execute(
+    storage: &mut dyn Storage,
+    env: &Env,
+    config: &MintApprovalConfig,
+    id: u64,
+    proposal: &mut MintProposal
+) -> Result<bool, ContractError> {
+    let mut approvals = 0u32;
+    for signer in &proposal.approvals {
+        if has_role(storage, Role::MintSigner, signer)? {
+            approvals += 1;
+        }
+    }
+    if approvals >= config.threshold {
+        mint_tokens(storage, env, &proposal.recipient, proposal.amount)?;
+        proposal.executed = true;
+    }
+    MINT_PROPOSALS.save(storage, id, proposal)?;
+    Ok(proposal.executed)
+}
+
+pub fn execute_propose_mint(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    amount: Uint128,
+    memo: Option<String>
+) -> Result<Response, ContractError> {
+    let config = load_for_mint_signer(deps.storage, &info)?;
+    let rcpt_addr = deps.api.addr_validate(&recipient)?;
+
+    let id = NEXT_MINT_PROPOSAL_ID.may_load(deps.storage)?.unwrap_or_default();
+    NEXT_MINT_PROPOSAL_ID.save(deps.storage, &(id + 1))?;
+    let mut proposal = MintProposal {
+        proposer: info.sender.clone(),
+        recipient: rcpt_addr,
+        amount,
+        memo,
+        approvals: vec![info.sender.clone()],
+        expires: config.proposal_duration.after(&env.block),
+        executed: false,
+    };
+    let executed = save_or_execute(deps.storage, &env, &config, id, &mut proposal)?;
+
+    let res = Response::new().add_attributes(
+        vec![
+            attr("action", "propose_mint"),
+            attr("id", id.to_string()),
+            attr("proposer", info.sender),
+            attr("to", recipient),
+            attr("amount", amount),
+            attr("executed", executed.to_string())
+        ]
+    );
+    Ok(res)
+}
+
+pub fn execute_approve_mint(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64
+) -> Result<Response, ContractError> {
+    let config = load_for_mint_signer(deps.storage, &info)?;
+    let mut proposal = MINT_PROPOSALS.may_load(deps.storage, id)?.ok_or(
+        ContractError::UnknownMintProposal {}
+    )?;
+    if proposal.executed {
+        return Err(ContractError::MintProposalExecuted {});
+    }
+    if proposal.expires.is_expired(&env.block) {
+        return Err(ContractError::MintProposalExpired {});
+    }
+    if proposal.approvals.contains(&info.sender) {
+        return Err(ContractError::AlreadyApproved {});
+    }
+    proposal.approvals.push(info.sender.clone());
+    let executed = save_or_execute(deps.storage, &env, &config, id, &mut proposal)?;
+
+    let res = Response::new().add_attributes(
+        vec![
+            attr("action", "approve_mint"),
+            attr("id", id.to_string()),
+            attr("approver", info.sender),
+            attr("executed", executed.to_string())
+        ]
+    );
+    Ok(res)
+}
+
+pub fn execute_update_mint_approval(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    config: Option<MintApprovalConfig>
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, Role::Admin, &info.sender)?;
+    match &config {
+        Some(config) => {
+            validate_mint_approval(deps.storage, config)?;
+            MINT_APPROVAL_CONFIG.save(deps.storage, config)?;
+        }
+        None => MINT_APPROVAL_CONFIG.remove(deps.storage),
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "update_mint_approval")
+        .add_attribute(
+            "threshold",
+            config.map(|c| c.threshold.to_string()).unwrap_or_else(|| "None".to_string())
+        );
+    Ok(res)
+}
+
+pub(crate) fn mint_proposal_response(id: u64, proposal: MintProposal) -> MintProposalResponse {
+    MintProposalResponse {
+        id,
+        proposer: proposal.proposer.into(),
+        recipient: proposal.recipient.into(),
+        amount: proposal.amount,
+        memo: proposal.memo,
+        approvals: proposal.approvals.into_iter().map(Into::into).collect(),
+        expires: proposal.expires,
+        executed: proposal.executed,
+    }
+}
+
+pub fn query_mint_approval(deps: Deps) -> StdResult<Option<MintApprovalConfig>> {
+    MINT_APPROVAL_CONFIG.may_load(deps.storage)
+}
+
+pub fn query_mint_proposal(deps: Deps, id: u64) -> StdResult<MintProposalResponse> {
+    let proposal = MINT_PROPOSALS.load(deps.storage, id)?;
+    Ok(mint_proposal_response(id, proposal))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::{ mock_dependencies, mock_env, mock_info };
+    use cw20::MinterResponse;
+    use cw_utils::Duration;
+
+    use crate::contract::{ execute, instantiate, query_balance, query_token_info };
+    use crate::enumerable::query_mint_proposals;
+    use crate::msg::{ ExecuteMsg, InstantiateMsg };
+    use crate::state::VestingSchedule;
+
+    fn do_instantiate(deps: DepsMut) {
+        let instantiate_msg = InstantiateMsg {
+            name: "Auto Gen".to_string(),
+            symbol: "AUTO".to_string(),
+            decimals: 3,
+            mint: Some(MinterResponse {
+                minter: "minter".into(),
+                cap: Some(Uint128::new(1000)),
+            }),
+            owner: Some("owner".into()),
+            mint_approval: Some(MintApprovalConfig {
+                threshold: 2,
+                proposal_duration: Duration::Height(100),
+            }),
+            mint_signers: Some(vec!["alice".into(), "bob".into(), "carol".into()]),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[]);
+        instantiate(deps, mock_env(), info, instantiate_msg).unwrap();
+    }
+
+    fn propose(amount: u128) -> ExecuteMsg {
+        ExecuteMsg::ProposeMint {
+            recipient: "treasury".into(),
+            amount: Uint128::new(amount),
+            memo: Some("grant".into()),
+        }
+    }
+
+    #[test]
+    fn mints_once_enough_signers_approve() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), propose(300));
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), propose(300)).unwrap();
+        assert_eq!(query_balance(deps.as_ref(), "treasury".into()).unwrap().balance, Uint128::zero());
+
+        let approve = ExecuteMsg::ApproveMint { id: 0 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), approve.clone());
+        assert_eq!(err.unwrap_err(), ContractError::AlreadyApproved {});
+        let res = execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), approve.clone()).unwrap();
+        assert!(res.attributes.contains(&attr("executed", "true")));
+        let balance = query_balance(deps.as_ref(), "treasury".into()).unwrap().balance;
+        assert_eq!(balance, Uint128::new(300));
+        assert_eq!(query_token_info(deps.as_ref()).unwrap().total_supply, Uint128::new(300));
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info("carol", &[]), approve).unwrap_err();
+        assert_eq!(err, ContractError::MintProposalExecuted {});
+
+        execute(deps.as_mut(), mock_env(), mock_info("carol", &[]), propose(50)).unwrap();
+        let proposals = query_mint_proposals(deps.as_ref(), Some(0), None).unwrap().proposals;
+        assert_eq!(proposals, vec![MintProposalResponse {
+            id: 1,
+            proposer: "carol".into(),
+            recipient: "treasury".into(),
+            amount: Uint128::new(50),
+            memo: Some("grant".into()),
+            approvals: vec!["carol".into()],
+            expires: Duration::Height(100).after(&mock_env().block),
+            executed: false,
+        }]);
+    }
+
+    #[test]
+    fn proposals_expire_and_respect_the_cap() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+        let mut env = mock_env();
+
+        // the cap is only checked when the mint executes
+        execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), propose(1001)).unwrap();
+        let approve = ExecuteMsg::ApproveMint { id: 0 };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("bob", &[]), approve).unwrap_err();
+        assert_eq!(err, ContractError::CannotExceedCap {});
+
+        execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), propose(100)).unwrap();
+        env.block.height += 100;
+        let approve = ExecuteMsg::ApproveMint { id: 1 };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("bob", &[]), approve).unwrap_err();
+        assert_eq!(err, ContractError::MintProposalExpired {});
+
+        // approvals of revoked signers no longer count
+        execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), propose(100)).unwrap();
+        let revoke = ExecuteMsg::RevokeRole { role: Role::MintSigner, address: "alice".into() };
+        execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), revoke).unwrap();
+        let approve = ExecuteMsg::ApproveMint { id: 2 };
+        execute(deps.as_mut(), env.clone(), mock_info("bob", &[]), approve.clone()).unwrap();
+        assert!(!query_mint_proposal(deps.as_ref(), 2).unwrap().executed);
+        execute(deps.as_mut(), env.clone(), mock_info("carol", &[]), approve).unwrap();
+        assert!(query_mint_proposal(deps.as_ref(), 2).unwrap().executed);
+
+        // alice was revoked, which leaves two signers
+        for threshold in [0, 3] {
+            let msg = ExecuteMsg::UpdateMintApproval {
+                config: Some(MintApprovalConfig { threshold, proposal_duration: Duration::Height(1) }),
+            };
+            let err = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::InvalidMintThreshold {});
+        }
+        let msg = ExecuteMsg::UpdateMintApproval { config: None };
+        execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+        let err = execute(deps.as_mut(), env, mock_info("bob", &[]), propose(100)).unwrap_err();
+        assert_eq!(err, ContractError::MintApprovalDisabled {});
+    }
+
+    #[test]
+    fn minter_cannot_bypass_the_threshold() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+        let minter = mock_info("minter", &[]);
+
+        let mint = ExecuteMsg::Mint { recipient: "minter".into(), amount: Uint128::new(100) };
+        let err = execute(deps.as_mut(), mock_env(), minter.clone(), mint.clone()).unwrap_err();
+        assert_eq!(err, ContractError::MintApprovalRequired {});
+        let msg = ExecuteMsg::MintVesting {
+            recipient: "minter".into(),
+            schedule: VestingSchedule::Cliff { amount: Uint128::new(100), release_at: 0 },
+        };
+        let err = execute(deps.as_mut(), mock_env(), minter.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::MintApprovalRequired {});
+
+        // delegated minters are held to the threshold as well
+        let msg = ExecuteMsg::SetMinter {
+            minter: "bridge".into(),
+            cap: None,
+            rate_limit: None,
+            expires: None,
+        };
+        execute(deps.as_mut(), mock_env(), minter.clone(), msg).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bridge", &[]), mint.clone());
+        assert_eq!(err.unwrap_err(), ContractError::MintApprovalRequired {});
+        assert_eq!(query_token_info(deps.as_ref()).unwrap().total_supply, Uint128::zero());
+
+        let msg = ExecuteMsg::UpdateMintApproval { config: None };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        execute(deps.as_mut(), mock_env(), minter, mint).unwrap();
+        assert_eq!(query_token_info(deps.as_ref()).unwrap().total_supply, Uint128::new(100));
+    }
+}
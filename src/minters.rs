@@ -0,0 +1,474 @@
+use cosmwasm_std::{
+    attr,
+    Addr,
+    BlockInfo,
+    Deps,
+    DepsMut,
+    Env,
+    MessageInfo,
+    Response,
+    StdError,
+    StdResult,
+    Storage,
+    Uint128,
+};
+use cw20::Expiration;
+
+use crate::error::ContractError;
+use crate::msg::PendingMinterResponse;
+use crate::roles::{ assert_primary_holder, has_role };
+use crate::state::{
+    MintRateLimit,
+    MinterAllowance,
+    MinterData,
+    PendingMinter,
+    Role,
+    MINTERS,
+    MINT_CAP_CONFIG,
+    PENDING_MINTER,
+    TOKEN_INFO,
+};
+
+/// Checks that a delegated minter may issue `amount` more tokens at this block and records
+/// the mint against its cap and rate limit.
+pub fn consume_minter_allowance(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    minter: &Addr,
+    amount: Uint128
+) -> Result<(), ContractError> {
+    let mut allowance = MINTERS.may_load(storage, minter)?.ok_or(ContractError::Unauthorized {})?;
+    if allowance.expires.is_expired(block) {
+        return Err(ContractError::MinterExpired {});
+    }
+
+    allowance.minted = allowance.minted.checked_add(amount).map_err(StdError::overflow)?;
+    if let Some(cap) = allowance.cap {
+        if allowance.minted > cap {
+            return Err(ContractError::MinterCapExceeded {});
+        }
+    }
+
+    if let Some(limit) = &allowance.rate_limit {
+        if block.height >= allowance.period_start.saturating_add(limit.period) {
+            allowance.period_start = block.height;
+            allowance.period_minted = Uint128::zero();
+        }
+        allowance.period_minted += amount;
+        if allowance.period_minted > limit.amount {
+            return Err(ContractError::MintRateLimitExceeded {});
+        }
+    }
+
+    MINTERS.save(storage, minter, &allowance)?;
+    Ok(())
+}
+
+/// Only the minter in `TOKEN_INFO` manages delegated minters and hands minting over, not the
+/// addresses granted the minter role.
+pub(crate) fn assert_token_minter(storage: &dyn Storage, sender: &Addr) -> Result<(), ContractError> {
+    assert_primary_holder(storage, Role::Minter, sender)
+}
+
+/// Grants or replaces minting rights for `minter`. Tokens it already minted keep counting
+/// against a new `cap`.
+pub fn execute_set_minter(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    minter: String,
+    cap: Option<Uint128>,
+    rate_limit: Option<MintRateLimit>,
+    expires: Option<Expiration>
+) -> Result<Response, ContractError> {
+    assert_token_minter(deps.storage, &info.sender)?;
+
+    let minter_addr = deps.api.addr_validate(&minter)?;
+    if let Some(limit) = &rate_limit {
+        if limit.period == 0 {
+            return Err(ContractError::InvalidMintRateLimit {});
+        }
+    }
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::InvalidExpiration {});
+    }
+
+    let previous = MINTERS.may_load(deps.storage, &minter_addr)?;
+    let allowance = MinterAllowance {
+        cap,
+        minted: previous.map(|p| p.minted).unwrap_or_default(),
+        rate_limit,
+        period_start: env.block.height,
+        period_minted: Uint128::zero(),
+        expires,
+    };
+    MINTERS.save(deps.storage, &minter_addr, &allowance)?;
+
+    let res = Response::new().add_attributes(
+        vec![
+            attr("action", "set_minter"),
+            attr("minter", minter),
+            attr("cap", cap.map(|c| c.to_string()).unwrap_or_else(|| "None".to_string()))
+        ]
+    );
+    Ok(res)
+}
+
+pub fn execute_remove_minter(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    minter: String
+) -> Result<Response, ContractError> {
+    assert_token_minter(deps.storage, &info.sender)?;
+
+    let minter_addr = deps.api.addr_validate(&minter)?;
+    MINTERS.remove(deps.storage, &minter_addr);
+
+    let res = Response::new().add_attributes(
+        vec![attr("action", "remove_minter"), attr("minter", minter)]
+    );
+    Ok(res)
+}
+
+pub fn execute_update_mint_cap(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    cap: Uint128
+) -> Result<Response, ContractError> {
+    let mut config = TOKEN_INFO.may_load(deps.storage)?.ok_or(ContractError::Unauthorized {})?;
+    let mut cap_config = MINT_CAP_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    let mint = config.mint.as_mut().ok_or(ContractError::Unauthorized {})?;
+    if
+        !has_role(deps.storage, Role::Minter, &info.sender)? &&
+        !has_role(deps.storage, Role::MintCapAdmin, &info.sender)?
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if cap < config.total_supply {
+        return Err(ContractError::CapBelowSupply {});
+    }
+    let old_cap = mint.cap;
+    match old_cap {
+        Some(old) if cap > old => {
+            if !matches!(cap_config.ceiling, Some(ceiling) if cap <= ceiling) {
+                return Err(ContractError::CapAboveCeiling {});
+            }
+        }
+        Some(old) if cap == old => {}
+        // a lowered cap is final, so the ceiling comes down with it
+        _ => {
+            cap_config.ceiling = Some(cap_config.ceiling.map_or(cap, |ceiling| ceiling.min(cap)));
+        }
+    }
+    mint.cap = Some(cap);
+    TOKEN_INFO.save(deps.storage, &config)?;
+    MINT_CAP_CONFIG.save(deps.storage, &cap_config)?;
+
+    let res = Response::new().add_attributes(
+        vec![
+            attr("action", "update_mint_cap"),
+            attr("old_cap", old_cap.map(|c| c.to_string()).unwrap_or_else(|| "None".to_string())),
+            attr("new_cap", cap)
+        ]
+    );
+    Ok(res)
+}
+
+pub fn execute_propose_minter(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_minter: String,
+    expires: Option<Expiration>
+) -> Result<Response, ContractError> {
+    assert_token_minter(deps.storage, &info.sender)?;
+
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::InvalidExpiration {});
+    }
+    let proposal = PendingMinter {
+        new_minter: deps.api.addr_validate(&new_minter)?,
+        expires,
+    };
+    PENDING_MINTER.save(deps.storage, &proposal)?;
+
+    let res = Response::new().add_attributes(
+        vec![
+            attr("action", "propose_minter"),
+            attr("new_minter", new_minter),
+            attr("expires", expires.to_string())
+        ]
+    );
+    Ok(res)
+}
+
+pub fn execute_accept_minter(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo
+) -> Result<Response, ContractError> {
+    let proposal = PENDING_MINTER.may_load(deps.storage)?.ok_or(ContractError::NoPendingMinter {})?;
+    if proposal.new_minter != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if proposal.expires.is_expired(&env.block) {
+        return Err(ContractError::MinterProposalExpired {});
+    }
+
+    let mut config = TOKEN_INFO.load(deps.storage)?;
+    let cap = config.mint.ok_or(ContractError::Unauthorized {})?.cap;
+    config.mint = Some(MinterData {
+        minter: proposal.new_minter,
+        cap,
+    });
+    TOKEN_INFO.save(deps.storage, &config)?;
+    PENDING_MINTER.remove(deps.storage);
+
+    let res = Response::new().add_attributes(
+        vec![attr("action", "accept_minter"), attr("new_minter", info.sender)]
+    );
+    Ok(res)
+}
+
+pub fn execute_cancel_minter_proposal(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo
+) -> Result<Response, ContractError> {
+    assert_token_minter(deps.storage, &info.sender)?;
+
+    let proposal = PENDING_MINTER.may_load(deps.storage)?.ok_or(ContractError::NoPendingMinter {})?;
+    PENDING_MINTER.remove(deps.storage);
+
+    let res = Response::new().add_attributes(
+        vec![attr("action", "cancel_minter_proposal"), attr("new_minter", proposal.new_minter)]
+    );
+    Ok(res)
+}
+
+pub fn query_pending_minter(deps: Deps) -> StdResult<Option<PendingMinterResponse>> {
+    let proposal = PENDING_MINTER.may_load(deps.storage)?;
+    Ok(
+        proposal.map(|p| PendingMinterResponse {
+            new_minter: p.new_minter.into(),
+            expires: p.expires,
+        })
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::{ mock_dependencies, mock_env, mock_info };
+    use cosmwasm_std::{ from_binary, DepsMut };
+    use cw20::MinterResponse;
+
+    use crate::contract::{ execute, instantiate, query, query_balance };
+    use crate::msg::{ AllMintersResponse, ExecuteMsg, InstantiateMsg, QueryMsg };
+
+    fn do_instantiate(deps: DepsMut, cap: Option<Uint128>) {
+        let instantiate_msg = InstantiateMsg {
+            name: "Auto Gen".to_string(),
+            symbol: "AUTO".to_string(),
+            decimals: 3,
+            initial_balances: vec![],
+            mint: Some(MinterResponse {
+                minter: "minter".into(),
+                cap,
+            }),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[]);
+        instantiate(deps, mock_env(), info, instantiate_msg).unwrap();
+    }
+
+    fn mint(deps: DepsMut, env: Env, sender: &str, amount: u128) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::Mint { recipient: "recipient".into(), amount: Uint128::new(amount) };
+        execute(deps, env, mock_info(sender, &[]), msg)
+    }
+
+    #[test]
+    fn delegated_minter_respects_own_cap_and_token_cap() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), Some(Uint128::new(1000)));
+
+        let msg = ExecuteMsg::SetMinter {
+            minter: "bridge".into(),
+            cap: Some(Uint128::new(500)),
+            rate_limit: None,
+            expires: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bridge", &[]), msg.clone());
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg).unwrap();
+
+        mint(deps.as_mut(), mock_env(), "bridge", 300).unwrap();
+        let err = mint(deps.as_mut(), mock_env(), "bridge", 201).unwrap_err();
+        assert_eq!(err, ContractError::MinterCapExceeded {});
+
+        // the token cap still applies on top of the minter cap
+        mint(deps.as_mut(), mock_env(), "minter", 600).unwrap();
+        let err = mint(deps.as_mut(), mock_env(), "bridge", 200).unwrap_err();
+        assert_eq!(err, ContractError::CannotExceedCap {});
+        mint(deps.as_mut(), mock_env(), "bridge", 100).unwrap();
+
+        let balance = query_balance(deps.as_ref(), "recipient".into()).unwrap().balance;
+        assert_eq!(balance, Uint128::new(1000));
+
+        let res: AllMintersResponse = from_binary(
+            &query(deps.as_ref(), mock_env(), QueryMsg::AllMinters {
+                start_after: None,
+                limit: None,
+            }).unwrap()
+        ).unwrap();
+        assert_eq!(res.minters.len(), 1);
+        assert_eq!(res.minters[0].minter, "bridge");
+        assert_eq!(res.minters[0].minted, Uint128::new(400));
+
+        // the legacy query still reports the primary minter
+        let minter: Option<MinterResponse> = from_binary(
+            &query(deps.as_ref(), mock_env(), QueryMsg::Minter {}).unwrap()
+        ).unwrap();
+        assert_eq!(minter.unwrap().minter, "minter");
+
+        let msg = ExecuteMsg::RemoveMinter { minter: "bridge".into() };
+        execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg).unwrap();
+        let err = mint(deps.as_mut(), mock_env(), "bridge", 1).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+
+    #[test]
+    fn delegated_minter_rate_limit_and_expiry() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), None);
+
+        let mut env = mock_env();
+        let msg = ExecuteMsg::SetMinter {
+            minter: "rewards".into(),
+            cap: None,
+            rate_limit: Some(MintRateLimit {
+                amount: Uint128::new(100),
+                period: 10,
+            }),
+            expires: Some(Expiration::AtHeight(env.block.height + 25)),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("minter", &[]), msg).unwrap();
+
+        mint(deps.as_mut(), env.clone(), "rewards", 60).unwrap();
+        let err = mint(deps.as_mut(), env.clone(), "rewards", 41).unwrap_err();
+        assert_eq!(err, ContractError::MintRateLimitExceeded {});
+
+        // a new period starts once `period` blocks have passed
+        env.block.height += 10;
+        mint(deps.as_mut(), env.clone(), "rewards", 100).unwrap();
+
+        env.block.height += 15;
+        let err = mint(deps.as_mut(), env, "rewards", 1).unwrap_err();
+        assert_eq!(err, ContractError::MinterExpired {});
+    }
+
+    #[test]
+    fn mint_cap_is_raised_within_ceiling_and_lowered_for_good() {
+        let mut deps = mock_dependencies();
+        let instantiate_msg = InstantiateMsg {
+            name: "Auto Gen".to_string(),
+            symbol: "AUTO".to_string(),
+            decimals: 3,
+            initial_balances: vec![],
+            mint: Some(MinterResponse {
+                minter: "minter".into(),
+                cap: Some(Uint128::new(1000)),
+            }),
+            mint_cap_admin: Some("admin".into()),
+            mint_cap_ceiling: Some(Uint128::new(5000)),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), instantiate_msg).unwrap();
+        mint(deps.as_mut(), mock_env(), "minter", 800).unwrap();
+
+        let update = |cap: u128| ExecuteMsg::UpdateMintCap { cap: Uint128::new(cap) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("other", &[]), update(2000));
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        let err = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), update(5001));
+        assert_eq!(err.unwrap_err(), ContractError::CapAboveCeiling {});
+        let err = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), update(799));
+        assert_eq!(err.unwrap_err(), ContractError::CapBelowSupply {});
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), update(4000)).unwrap();
+        assert_eq!(res.attributes[1], attr("old_cap", "1000"));
+        assert_eq!(res.attributes[2], attr("new_cap", "4000"));
+        // re-applying the current cap leaves the ceiling alone
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), update(4000)).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("admin", &[]), update(5000)).unwrap();
+
+        execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), update(2000)).unwrap();
+        let minter: Option<MinterResponse> = from_binary(
+            &query(deps.as_ref(), mock_env(), QueryMsg::Minter {}).unwrap()
+        ).unwrap();
+        assert_eq!(minter.unwrap().cap, Some(Uint128::new(2000)));
+
+        // the lowered cap became the new ceiling
+        let err = execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), update(3000));
+        assert_eq!(err.unwrap_err(), ContractError::CapAboveCeiling {});
+    }
+
+    #[test]
+    fn minter_handover_needs_acceptance() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), Some(Uint128::new(1000)));
+
+        let msg = ExecuteMsg::ProposeMinter { new_minter: "governance".into(), expires: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("governance", &[]), msg.clone());
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg).unwrap();
+
+        // the old minter keeps minting until the proposal is accepted
+        mint(deps.as_mut(), mock_env(), "minter", 10).unwrap();
+        let pending = query_pending_minter(deps.as_ref()).unwrap().unwrap();
+        assert_eq!(pending.new_minter, "governance");
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info("other", &[]), ExecuteMsg::AcceptMinter {});
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("governance", &[]), ExecuteMsg::AcceptMinter {}).unwrap();
+
+        let minter: Option<MinterResponse> = from_binary(
+            &query(deps.as_ref(), mock_env(), QueryMsg::Minter {}).unwrap()
+        ).unwrap();
+        assert_eq!(minter, Some(MinterResponse {
+            minter: "governance".into(),
+            cap: Some(Uint128::new(1000)),
+        }));
+        assert_eq!(query_pending_minter(deps.as_ref()).unwrap(), None);
+        let err = mint(deps.as_mut(), mock_env(), "minter", 10).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+
+    #[test]
+    fn minter_proposal_expires_and_can_be_cancelled() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), None);
+
+        let mut env = mock_env();
+        let msg = ExecuteMsg::ProposeMinter {
+            new_minter: "governance".into(),
+            expires: Some(Expiration::AtHeight(env.block.height + 5)),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("minter", &[]), msg).unwrap();
+
+        env.block.height += 5;
+        let governance = mock_info("governance", &[]);
+        let err = execute(deps.as_mut(), env.clone(), governance.clone(), ExecuteMsg::AcceptMinter {});
+        assert_eq!(err.unwrap_err(), ContractError::MinterProposalExpired {});
+
+        let msg = ExecuteMsg::CancelMinterProposal {};
+        execute(deps.as_mut(), env.clone(), mock_info("minter", &[]), msg).unwrap();
+        let err = execute(deps.as_mut(), env, governance, ExecuteMsg::AcceptMinter {});
+        assert_eq!(err.unwrap_err(), ContractError::NoPendingMinter {});
+    }
+}
@@ -1,9 +1,18 @@
 use cosmwasm_schema::{ cw_serde, QueryResponses };
-use cosmwasm_std::{ StdError, StdResult, Uint128, Binary };
+use cosmwasm_std::{ Order, StdError, StdResult, Uint128, Binary };
 use cw20::{ Cw20Coin, Logo, MinterResponse };
 use schemars::JsonSchema;
 use serde::{ Deserialize, Serialize };
-use cw_utils::Expiration;
+use cw_utils::{ Duration, Expiration };
+
+use crate::state::{
+    MintApprovalConfig,
+    MintRateLimit,
+    Role,
+    SnapshotStrategy,
+    TimelockOperation,
+    VestingSchedule,
+};
 
 #[cw_serde]
 pub enum ExecuteMsg {
@@ -12,6 +21,15 @@ pub enum ExecuteMsg {
         recipient: String,
         amount: Uint128,
     },
+    /// Moves tokens from the sender to several recipients at once. Each recipient may appear
+    /// only once.
+    BatchTransfer {
+        transfers: Vec<Cw20Coin>,
+    },
+    /// Like Send, for several contracts at once. Each contract may appear only once.
+    BatchSend {
+        sends: Vec<BatchSendItem>,
+    },
     /// Burn is a base message to destroy tokens forever
     Burn {
         amount: Uint128,
@@ -25,7 +43,9 @@ pub enum ExecuteMsg {
     },
     /// Only with "approval" extension. Allows spender to access an additional amount tokens
     /// from the owner's (env.sender) account. If expires is Some(), overwrites current allowance
-    /// expiration with this one.
+    /// expiration with this one. An allowance of Uint128::MAX is unlimited and is not reduced
+    /// by spending. Only an amount of exactly Uint128::MAX makes the allowance unlimited;
+    /// other increases that would overflow fail.
     IncreaseAllowance {
         spender: String,
         amount: Uint128,
@@ -39,6 +59,46 @@ pub enum ExecuteMsg {
         amount: Uint128,
         expires: Option<Expiration>,
     },
+    /// Only with "approval" extension. Overwrites the spender's access of tokens from the
+    /// owner's (env.sender) account with amount, removing it when amount is zero. If
+    /// expected_current is Some(), fails unless that is the allowance right now, which guards
+    /// against the spender spending the old allowance just before the new one is set.
+    SetAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+        expected_current: Option<Uint128>,
+    },
+    /// Removes a page of the allowances granted from the sender's account, in the same order
+    /// as AllAllowances. The number removed is returned as the `count` attribute; call again
+    /// until it comes back lower than the limit.
+    RevokeAllAllowances {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Removes the allowances granted from the sender's account to the given spenders.
+    RevokeAllowances {
+        spenders: Vec<String>,
+    },
+    /// Scans up to limit allowances granted from the owner's account, resuming where the last
+    /// call stopped, and removes the expired ones. Callable by anyone. This is the only way
+    /// expired allowances are removed: spending from one fails and querying one reports zero,
+    /// but both leave it stored.
+    PruneExpiredAllowances {
+        owner: String,
+        limit: Option<u32>,
+    },
+    /// Sets the allowance of spender on the account of the owner of `owner_pubkey` to amount,
+    /// on behalf of that owner. `signature` is the owner's secp256k1 signature over the SHA-256
+    /// hash of the JSON encoded `PermitPayload`, so anyone can relay it.
+    Permit {
+        owner_pubkey: Binary,
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+        nonce: u64,
+        signature: Binary,
+    },
     /// Only with "approval" extension. Transfers amount tokens from owner -> recipient
     /// if `env.sender` has sufficient pre-approval.
     TransferFrom {
@@ -58,9 +118,68 @@ pub enum ExecuteMsg {
         recipient: String,
         amount: Uint128,
     },
+    /// Only callable by the minter. Passing None renounces minting for good; handing minting
+    /// over to another address goes through ProposeMinter and AcceptMinter instead.
     UpdateMinter {
         new_minter: Option<String>,
     },
+    /// Gives the voting power of the sender's whole balance, now and in the future, to
+    /// `delegatee`. Delegate to yourself to vote with your own tokens.
+    Delegate {
+        delegatee: String,
+    },
+    /// Only callable by the minter. Mints `schedule`'s amount to `recipient` and locks it
+    /// until it vests. Fails if the recipient already has a vesting schedule.
+    MintVesting {
+        recipient: String,
+        schedule: VestingSchedule,
+    },
+    /// Only callable by the minter or the mint cap admin. Lowering the cap is final, raising
+    /// it is only possible up to the ceiling set at instantiation.
+    UpdateMintCap {
+        cap: Uint128,
+    },
+    /// Only callable by the minter. Offers minting rights to `new_minter`, replacing any
+    /// earlier proposal. Nothing changes until the new minter accepts.
+    ProposeMinter {
+        new_minter: String,
+        expires: Option<Expiration>,
+    },
+    /// Only callable by the proposed minter, before the proposal expires.
+    AcceptMinter {},
+    /// Only callable by the minter. Withdraws a pending proposal.
+    CancelMinterProposal {},
+    /// Only callable by the minter. Lets another address mint up to `cap` tokens in total,
+    /// optionally rate limited and expiring. Replaces any previous settings for that address.
+    SetMinter {
+        minter: String,
+        cap: Option<Uint128>,
+        rate_limit: Option<MintRateLimit>,
+        expires: Option<Expiration>,
+    },
+    /// Only callable by the minter. Revokes minting rights granted with SetMinter.
+    RemoveMinter {
+        minter: String,
+    },
+    /// Only callable by a mint signer. Proposes minting `amount` to `recipient`, approved by the
+    /// sender. The tokens are minted once enough signers approved the proposal.
+    ProposeMint {
+        recipient: String,
+        amount: Uint128,
+        memo: Option<String>,
+    },
+    /// Only callable by a mint signer, before the proposal expires. Mints the proposed amount
+    /// if this approval reaches the threshold.
+    ApproveMint {
+        id: u64,
+    },
+    /// Only callable by an admin. Sets how many mint signers have to approve a mint proposal,
+    /// at most the number of mint signers.
+    /// While set, tokens are only minted through proposals and Mint and MintVesting are refused.
+    /// None disables mint proposals.
+    UpdateMintApproval {
+        config: Option<MintApprovalConfig>,
+    },
     BurnFrom {
         owner: String,
         amount: Uint128,
@@ -78,6 +197,189 @@ pub enum ExecuteMsg {
     },
     /// If set as the "marketing" role on the contract, upload a new URL, SVG, or PNG for the token
     UploadLogo(Logo),
+    /// Only callable by the pause guardian or an admin. Halts the given kinds of actions, or
+    /// all of them if `actions` is None.
+    Pause {
+        actions: Option<Vec<PausableAction>>,
+    },
+    /// Only callable by the pause guardian or an admin. Resumes the given kinds of actions,
+    /// or all of them if `actions` is None.
+    Unpause {
+        actions: Option<Vec<PausableAction>>,
+    },
+    /// Only callable by the pause guardian or an admin. Hands the role over, or removes it if
+    /// None.
+    UpdatePauseGuardian {
+        guardian: Option<String>,
+    },
+    /// Only callable by the launch admin or an admin, before trading opens. Adds and removes
+    /// addresses from the allowlist of addresses that may move tokens.
+    UpdateTransferAllowlist {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Only callable by the launch admin or an admin. Lifts the allowlist restriction for good.
+    OpenTrading {},
+    /// Only callable by the launch admin or an admin. Replaces the anti-whale limits, None
+    /// removing a limit. Once trading is open limits can only be raised or removed.
+    UpdateLaunchLimits {
+        max_tx_amount: Option<Uint128>,
+        max_wallet_balance: Option<Uint128>,
+    },
+    /// Only callable by the launch admin or an admin. Adds and removes addresses from the
+    /// launch limit exemption list. Once trading is open addresses can only be added.
+    UpdateLimitExemptions {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Only callable by the compliance admin. Stops the address from sending or receiving
+    /// tokens, directly or through allowances, until it is unfrozen.
+    Freeze {
+        address: String,
+        reason: String,
+    },
+    /// Only callable by the compliance admin.
+    Unfreeze {
+        address: String,
+    },
+    /// Only callable by the compliance admin. Hands the role over, or removes it if None.
+    UpdateComplianceAdmin {
+        admin: Option<String>,
+    },
+    /// Only callable by the fee admin or an admin. Replaces the transfer fee schedule. A zero
+    /// rate disables the fee.
+    UpdateTransferFee {
+        fee: TransferFeeInfo,
+    },
+    /// Only callable by the fee admin or an admin. Adds and removes addresses from the fee
+    /// exemption list.
+    UpdateFeeExemptions {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Only callable by the owner. Offers ownership to `new_owner`, replacing any earlier
+    /// offer. Nothing changes until the new owner accepts.
+    TransferOwnership {
+        new_owner: String,
+        expires: Option<Expiration>,
+    },
+    /// Only callable by the proposed owner, before the offer expires.
+    AcceptOwnership {},
+    /// Only callable by the owner. Leaves the token without an owner for good, dropping any
+    /// pending offer.
+    RenounceOwnership {},
+    /// Only callable by holders of the role's admin role. Lets `address` act in `role`
+    /// alongside its primary holder, who alone can hand the primary slot over.
+    GrantRole {
+        role: Role,
+        address: String,
+    },
+    /// Only callable by holders of the role's admin role. Takes a granted role away. The
+    /// primary holder is changed with the message that manages it, e.g. UpdatePauseGuardian.
+    RevokeRole {
+        role: Role,
+        address: String,
+    },
+    /// Gives up a role granted to the sender.
+    RenounceRole {
+        role: Role,
+    },
+    /// Only callable by holders of the role's current admin role. Makes holders of
+    /// `admin_role` the ones granting and revoking `role`.
+    SetRoleAdmin {
+        role: Role,
+        admin_role: Role,
+    },
+    /// Only callable by an admin. Sets how long ScheduleOperation delays privileged changes,
+    /// or lets them apply immediately again if None. Needs scheduling itself while a delay
+    /// is set.
+    UpdateTimelockDelay {
+        delay: Option<Duration>,
+    },
+    /// Queues a privileged change until the timelock delay has passed. Only callable by
+    /// whoever may make the change directly, whose privileges it then runs with.
+    ScheduleOperation {
+        operation: TimelockOperation,
+    },
+    /// Only callable by the proposer or an admin, until the operation is executed.
+    CancelOperation {
+        id: u64,
+    },
+    /// Applies a scheduled operation once its eta has passed. Callable by anyone.
+    ExecuteOperation {
+        id: u64,
+    },
+    /// Only callable by a checkpointer or an admin, with the Selected snapshot strategy. Makes
+    /// balances at the current height queryable with BalanceAt.
+    Checkpoint {},
+    /// Drops balance history before `before_height`, which has to be outside the retention
+    /// window, scanning up to `limit` history entries. Anyone can call this, again until
+    /// nothing is left to prune. BalanceAt fails for pruned heights.
+    PruneBalanceHistory {
+        before_height: u64,
+        limit: Option<u32>,
+    },
+    /// Drops supply history before `before_height` under the same retention window, up to
+    /// `limit` records at a time. Anyone can call this. TotalSupplyAt fails for pruned heights.
+    PruneSupplyHistory {
+        before_height: u64,
+        limit: Option<u32>,
+    },
+    /// Processes up to `limit` entries of state migration work left over from `migrate`.
+    /// Anyone can call this until no migration steps remain.
+    ContinueMigration {
+        limit: Option<u32>,
+    },
+}
+
+impl ExecuteMsg {
+    /// Whether this message has to go through ScheduleOperation while a timelock delay is set
+    pub fn is_timelocked(&self) -> bool {
+        matches!(
+            self,
+            ExecuteMsg::ProposeMinter { .. } |
+                ExecuteMsg::SetMinter { .. } |
+                ExecuteMsg::UpdateMintCap { .. } |
+                ExecuteMsg::GrantRole { .. } |
+                ExecuteMsg::SetRoleAdmin { .. } |
+                ExecuteMsg::UpdateTimelockDelay { .. } |
+                ExecuteMsg::UpdateMintApproval { .. } |
+                ExecuteMsg::UpdateMinter { .. } |
+                ExecuteMsg::UpdateMarketing { marketing: Some(_), .. } |
+                ExecuteMsg::UpdatePauseGuardian { .. } |
+                ExecuteMsg::UpdateComplianceAdmin { .. } |
+                ExecuteMsg::TransferOwnership { .. } |
+                ExecuteMsg::AcceptOwnership {} |
+                ExecuteMsg::RenounceOwnership {}
+        )
+    }
+}
+
+#[cw_serde]
+pub struct BatchSendItem {
+    pub contract: String,
+    pub amount: Uint128,
+    pub msg: Binary,
+}
+
+/// The groups of actions the pause guardian can halt independently
+#[cw_serde]
+pub enum PausableAction {
+    /// Transfer, Send and their batch and allowance based variants
+    Transfer,
+    Mint,
+    /// Burn and BurnFrom
+    Burn,
+}
+
+#[cw_serde]
+pub struct TransferFeeInfo {
+    /// Fee charged on every transfer, in basis points of the transferred amount
+    pub rate_bps: u16,
+    /// Upper bound on the fee charged for a single transfer
+    pub max_fee: Option<Uint128>,
+    /// Receives the collected fees. Fees are burned if this is None
+    pub collector: Option<String>,
 }
 
 #[cw_serde]
@@ -96,7 +398,50 @@ pub struct InstantiateMsg {
     pub decimals: u8,
     pub initial_balances: Vec<Cw20Coin>,
     pub mint: Option<MinterResponse>,
+    /// The address allowed to change the token's configuration besides the dedicated admins
+    pub owner: Option<String>,
     pub marketing: Option<InstantiateMarketingInfo>,
+    /// The address allowed to pause transfers, minting and burning
+    pub pause_guardian: Option<String>,
+    pub transfer_fee: Option<TransferFeeInfo>,
+    /// The address allowed to change the transfer fee and its exemptions
+    pub fee_admin: Option<String>,
+    /// Addresses never charged the transfer fee, e.g. DEX pairs and the router
+    pub fee_exemptions: Option<Vec<String>>,
+    /// The address allowed to freeze accounts
+    pub compliance_admin: Option<String>,
+    /// The address allowed to manage the transfer allowlist and open trading
+    pub launch_admin: Option<String>,
+    /// Restricts transfers to `transfer_allowlist` until the launch admin opens trading
+    pub allowlist_only: Option<bool>,
+    pub transfer_allowlist: Option<Vec<String>>,
+    /// The most a single transfer or mint may credit to one account
+    pub max_tx_amount: Option<Uint128>,
+    /// The most any account may hold after being credited
+    pub max_wallet_balance: Option<Uint128>,
+    /// Addresses the limits above do not apply to, e.g. DEX pairs and the fee collector
+    pub limit_exemptions: Option<Vec<String>>,
+    /// An address besides the minter allowed to change the mint cap
+    pub mint_cap_admin: Option<String>,
+    /// The highest the mint cap can ever be raised to. None means it can only be lowered
+    pub mint_cap_ceiling: Option<Uint128>,
+    /// Enables mints approved by several of `mint_signers`
+    pub mint_approval: Option<MintApprovalConfig>,
+    pub mint_signers: Option<Vec<String>>,
+    /// Makes minter changes, cap changes and role grants wait this long through the timelock
+    pub timelock_delay: Option<Duration>,
+    /// How balance history is recorded. Defaults to every block
+    pub balance_snapshot_strategy: Option<SnapshotStrategy>,
+    /// How many blocks of history are kept before it can be pruned. None keeps it forever
+    pub history_retention: Option<u64>,
+    /// Locks part of `initial_balances` under vesting schedules
+    pub initial_vesting: Option<Vec<VestingAccount>>,
+}
+
+#[cw_serde]
+pub struct VestingAccount {
+    pub address: String,
+    pub schedule: VestingSchedule,
 }
 
 impl InstantiateMsg {
@@ -159,20 +504,108 @@ pub enum QueryMsg {
     #[returns(Uint128)] TotalSupplyAt {
         block: u64,
     },
+    /// Returns the total supply, minted and burned amounts of the blocks the supply changed in,
+    /// between `start_after` and `end_before`. Oldest first unless `order` is descending. Fails
+    /// until the history kept before 0.3.0 has been migrated.
+    #[returns(SupplyHistoryResponse)]
+    SupplyHistory {
+        start_after: Option<u64>,
+        end_before: Option<u64>,
+        limit: Option<u32>,
+        order: Option<OrderBy>,
+    },
     /// Returns metadata on the contract - name, decimals, supply, etc.
     #[returns(cw20::TokenInfoResponse)]
     TokenInfo {},
+    /// Returns the number of accounts holding a non-zero balance at the start of the given
+    /// block height.
+    #[returns(HolderCountResponse)]
+    HolderCountAt {
+        height: u64,
+    },
+    /// Returns how balance history is recorded and how much of it was pruned.
+    #[returns(BalanceHistoryResponse)]
+    BalanceHistory {},
+    /// Returns the nonce the owner has to sign into its next permit.
+    #[returns(NonceResponse)]
+    Nonce {
+        owner: String,
+    },
+    /// Returns who the given address delegated its votes to, if anyone.
+    #[returns(DelegationResponse)]
+    Delegation {
+        address: String,
+    },
+    /// Returns the votes currently delegated to the given address.
+    #[returns(VotingPowerResponse)]
+    VotingPower {
+        address: String,
+    },
+    /// Returns the votes delegated to the given address at the start of the given block height.
+    #[returns(VotingPowerResponse)]
+    VotingPowerAt {
+        address: String,
+        height: u64,
+    },
+    /// Returns the sum of all delegated votes at the start of the given block height.
+    #[returns(VotingPowerResponse)]
+    TotalVotingPowerAt {
+        height: u64,
+    },
     /// Only with "mintable" extension.
     /// Returns who can mint and the hard cap on maximum tokens after minting.
     #[returns(cw20::MinterResponse)]
     Minter {},
+    /// Returns the vesting schedule of the given address, if any, with the amount still locked.
+    #[returns(VestingScheduleResponse)]
+    VestingSchedule {
+        address: String,
+    },
+    /// Returns the part of the balance of the given address that is not locked by vesting.
+    #[returns(cw20::BalanceResponse)]
+    SpendableBalance {
+        address: String,
+    },
+    /// Returns the minter handover waiting to be accepted, if any.
+    #[returns(Option<PendingMinterResponse>)]
+    PendingMinter {},
+    /// Returns the addresses the minter delegated minting rights to, with their caps and
+    /// rate limits. Supports pagination.
+    #[returns(AllMintersResponse)]
+    AllMinters {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns how many mint signers have to approve a mint proposal, if mint proposals are
+    /// enabled.
+    #[returns(Option<MintApprovalConfig>)]
+    MintApproval {},
+    #[returns(MintProposalResponse)]
+    MintProposal {
+        id: u64,
+    },
+    /// Returns mint proposals, whether open, expired or executed. Supports pagination.
+    #[returns(MintProposalsResponse)]
+    MintProposals {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     /// Only with "allowance" extension.
-    /// Returns how much spender can use from owner account, 0 if unset.
+    /// Returns how much spender can use from owner account, 0 if unset or expired and
+    /// Uint128::MAX if unlimited.
     #[returns(cw20::AllowanceResponse)]
     Allowance {
         owner: String,
         spender: String,
     },
+    /// Returns the allowance as it was at the start of the given block height. History is
+    /// only kept from 0.2.0 on.
+    #[returns(cw20::AllowanceResponse)]
+    AllowanceAt {
+        owner: String,
+        spender: String,
+        height: u64,
+    },
     /// Only with "enumerable" extension (and "allowances")
     /// Returns all allowances this owner has approved. Supports pagination.
     #[returns(cw20::AllAllowancesResponse)]
@@ -180,6 +613,10 @@ pub enum QueryMsg {
         owner: String,
         start_after: Option<String>,
         limit: Option<u32>,
+        /// Set to false to leave out expired allowances. Defaults to true. A page then scans
+        /// at most ten entries per allowance in `limit`, so it may come back short, or empty,
+        /// while more allowances follow; prune expired allowances to avoid this
+        include_expired: Option<bool>,
     },
     /// Only with "enumerable" extension (and "allowances")
     /// Returns all allowances this spender has been granted. Supports pagination.
@@ -188,6 +625,10 @@ pub enum QueryMsg {
         spender: String,
         start_after: Option<String>,
         limit: Option<u32>,
+        /// Set to false to leave out expired allowances. Defaults to true. A page then scans
+        /// at most ten entries per allowance in `limit`, so it may come back short, or empty,
+        /// while more allowances follow; prune expired allowances to avoid this
+        include_expired: Option<bool>,
     },
     /// Only with "enumerable" extension
     /// Returns all accounts that have balances. Supports pagination.
@@ -206,10 +647,296 @@ pub enum QueryMsg {
     /// contract.
     #[returns(cw20::DownloadLogoResponse)]
     DownloadLogo {},
+    /// Returns whether the given address may act in the role, as its primary holder, by grant,
+    /// or as an admin for the roles admins act in.
+    #[returns(HasRoleResponse)]
+    HasRole {
+        role: Role,
+        address: String,
+    },
+    /// Returns the primary holder of the role, the addresses granted it and the role that
+    /// administers it. Supports pagination over the granted addresses.
+    #[returns(RoleMembersResponse)]
+    RoleMembers {
+        role: Role,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the timelock delay, if one is set.
+    #[returns(TimelockResponse)]
+    Timelock {},
+    /// Returns the privileged changes waiting in the timelock, oldest first. Supports
+    /// pagination.
+    #[returns(PendingOperationsResponse)]
+    PendingOperations {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the owner and the ownership transfer waiting to be accepted, if any.
+    #[returns(OwnershipResponse)]
+    Ownership {},
+    /// Returns the pause guardian and which actions are currently paused.
+    #[returns(PauseInfoResponse)]
+    PauseInfo {},
+    /// Returns the launch admin and whether transfers are still restricted to the allowlist.
+    #[returns(TransferRestrictionResponse)]
+    TransferRestriction {},
+    /// Returns the addresses allowed to move tokens before trading opens. Supports pagination.
+    #[returns(TransferAllowlistResponse)]
+    TransferAllowlist {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the anti-whale limits currently in force.
+    #[returns(LaunchLimitsResponse)]
+    LaunchLimits {},
+    /// Returns the addresses exempt from the anti-whale limits. Supports pagination.
+    #[returns(LimitExemptionsResponse)]
+    LimitExemptions {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns whether the given address is frozen, and why.
+    #[returns(IsFrozenResponse)]
+    IsFrozen {
+        address: String,
+    },
+    /// Returns the frozen addresses with the reasons they were frozen for. Supports pagination.
+    #[returns(AllFrozenResponse)]
+    AllFrozen {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the transfer fee schedule and the fee admin.
+    #[returns(TransferFeeResponse)]
+    TransferFee {},
+    /// Returns the addresses exempt from the transfer fee. Supports pagination.
+    #[returns(FeeExemptionsResponse)]
+    FeeExemptions {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[cw_serde]
+pub struct TimelockResponse {
+    pub delay: Option<Duration>,
+}
+
+#[cw_serde]
+pub struct PendingOperation {
+    pub id: u64,
+    pub operation: TimelockOperation,
+    pub proposer: String,
+    pub eta: Expiration,
+}
+
+#[cw_serde]
+pub struct PendingOperationsResponse {
+    pub operations: Vec<PendingOperation>,
+}
+
+#[cw_serde]
+pub struct HasRoleResponse {
+    pub has_role: bool,
+}
+
+#[cw_serde]
+pub struct RoleMembersResponse {
+    pub primary: Option<String>,
+    pub members: Vec<String>,
+    pub admin_role: Role,
+}
+
+#[cw_serde]
+pub struct OwnershipResponse {
+    pub owner: Option<String>,
+    pub pending_owner: Option<String>,
+    pub pending_expiry: Option<Expiration>,
+}
+
+#[cw_serde]
+pub struct PauseInfoResponse {
+    pub guardian: Option<String>,
+    pub transfers_paused: bool,
+    pub mint_paused: bool,
+    pub burn_paused: bool,
+}
+
+#[cw_serde]
+pub struct TransferRestrictionResponse {
+    pub admin: Option<String>,
+    pub allowlist_only: bool,
+}
+
+#[cw_serde]
+pub struct TransferAllowlistResponse {
+    pub addresses: Vec<String>,
+}
+
+#[cw_serde]
+pub struct LaunchLimitsResponse {
+    pub max_tx_amount: Option<Uint128>,
+    pub max_wallet_balance: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct LimitExemptionsResponse {
+    pub addresses: Vec<String>,
+}
+
+#[cw_serde]
+pub struct IsFrozenResponse {
+    pub frozen: bool,
+    pub reason: Option<String>,
+}
+
+#[cw_serde]
+pub struct FrozenAccount {
+    pub address: String,
+    pub reason: String,
+}
+
+#[cw_serde]
+pub struct AllFrozenResponse {
+    pub accounts: Vec<FrozenAccount>,
+}
+
+/// The document an owner signs for `ExecuteMsg::Permit`. It is bound to one chain and one
+/// token contract, and `nonce` must match the owner's current nonce.
+#[cw_serde]
+pub struct PermitPayload {
+    pub chain_id: String,
+    pub contract: String,
+    pub spender: String,
+    pub amount: Uint128,
+    pub expires: Option<Expiration>,
+    pub nonce: u64,
+}
+
+#[cw_serde]
+pub struct NonceResponse {
+    pub nonce: u64,
+}
+
+#[cw_serde]
+pub struct DelegationResponse {
+    pub delegatee: Option<String>,
+}
+
+#[cw_serde]
+pub struct VotingPowerResponse {
+    pub power: Uint128,
+}
+
+#[cw_serde]
+pub struct HolderCountResponse {
+    pub count: u64,
+}
+
+#[cw_serde]
+pub enum OrderBy {
+    Ascending,
+    Descending,
+}
+
+impl From<OrderBy> for Order {
+    fn from(order: OrderBy) -> Self {
+        match order {
+            OrderBy::Ascending => Order::Ascending,
+            OrderBy::Descending => Order::Descending,
+        }
+    }
+}
+
+#[cw_serde]
+pub struct SupplyHistoryEntry {
+    pub height: u64,
+    pub total_supply: Uint128,
+    pub minted: Uint128,
+    pub burned: Uint128,
+}
+
+#[cw_serde]
+pub struct SupplyHistoryResponse {
+    pub entries: Vec<SupplyHistoryEntry>,
+    /// The total supply cannot be queried before this height
+    pub pruned_before: u64,
+}
+
+#[cw_serde]
+pub struct BalanceHistoryResponse {
+    pub strategy: SnapshotStrategy,
+    pub retention: Option<u64>,
+    /// Balances cannot be queried before this height
+    pub pruned_before: u64,
+}
+
+#[cw_serde]
+pub struct VestingScheduleResponse {
+    pub schedule: Option<VestingSchedule>,
+    pub locked: Uint128,
+}
+
+#[cw_serde]
+pub struct PendingMinterResponse {
+    pub new_minter: String,
+    pub expires: Expiration,
+}
+
+#[cw_serde]
+pub struct MinterInfo {
+    pub minter: String,
+    pub cap: Option<Uint128>,
+    pub minted: Uint128,
+    pub rate_limit: Option<MintRateLimit>,
+    pub expires: Expiration,
+}
+
+#[cw_serde]
+pub struct AllMintersResponse {
+    pub minters: Vec<MinterInfo>,
+}
+
+#[cw_serde]
+pub struct MintProposalResponse {
+    pub id: u64,
+    pub proposer: String,
+    pub recipient: String,
+    pub amount: Uint128,
+    pub memo: Option<String>,
+    pub approvals: Vec<String>,
+    pub expires: Expiration,
+    pub executed: bool,
+}
+
+#[cw_serde]
+pub struct MintProposalsResponse {
+    pub proposals: Vec<MintProposalResponse>,
+}
+
+#[cw_serde]
+pub struct TransferFeeResponse {
+    pub admin: Option<String>,
+    pub rate_bps: u16,
+    pub max_fee: Option<Uint128>,
+    /// None means fees are burned
+    pub collector: Option<String>,
+}
+
+#[cw_serde]
+pub struct FeeExemptionsResponse {
+    pub addresses: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, JsonSchema)]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    /// How many storage entries of paged migration work to process during the migrate call
+    /// itself. Whatever is left is finished with `ExecuteMsg::ContinueMigration`.
+    pub batch_limit: Option<u32>,
+    /// Sets the owner, e.g. for tokens instantiated before ownership existed
+    pub owner: Option<String>,
+}
 
 #[cfg(test)]
 mod tests {
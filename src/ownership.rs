@@ -0,0 +1,172 @@
+use cosmwasm_std::{ attr, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage };
+use cw20::Expiration;
+
+use crate::error::ContractError;
+use crate::msg::OwnershipResponse;
+use crate::state::{ Ownership, OWNER };
+
+fn load_for_owner(storage: &dyn Storage, info: &MessageInfo) -> Result<Ownership, ContractError> {
+    let ownership = OWNER.may_load(storage)?.unwrap_or_default();
+    if ownership.owner.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(ownership)
+}
+
+pub fn execute_transfer_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_owner: String,
+    expires: Option<Expiration>
+) -> Result<Response, ContractError> {
+    let mut ownership = load_for_owner(deps.storage, &info)?;
+
+    let expires = expires.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::InvalidExpiration {});
+    }
+    ownership.pending_owner = Some(deps.api.addr_validate(&new_owner)?);
+    ownership.pending_expiry = Some(expires);
+    OWNER.save(deps.storage, &ownership)?;
+
+    let res = Response::new().add_attributes(
+        vec![
+            attr("action", "transfer_ownership"),
+            attr("new_owner", new_owner),
+            attr("expires", expires.to_string())
+        ]
+    );
+    Ok(res)
+}
+
+pub fn execute_accept_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo
+) -> Result<Response, ContractError> {
+    let mut ownership = OWNER.may_load(deps.storage)?.unwrap_or_default();
+    let pending_owner = ownership.pending_owner.take().ok_or(ContractError::NoPendingOwner {})?;
+    if pending_owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if matches!(ownership.pending_expiry.take(), Some(expires) if expires.is_expired(&env.block)) {
+        return Err(ContractError::OwnershipTransferExpired {});
+    }
+    ownership.owner = Some(pending_owner);
+    OWNER.save(deps.storage, &ownership)?;
+
+    let res = Response::new().add_attributes(
+        vec![attr("action", "accept_ownership"), attr("new_owner", info.sender)]
+    );
+    Ok(res)
+}
+
+pub fn execute_renounce_ownership(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo
+) -> Result<Response, ContractError> {
+    load_for_owner(deps.storage, &info)?;
+    OWNER.save(deps.storage, &Ownership::default())?;
+
+    Ok(Response::new().add_attribute("action", "renounce_ownership"))
+}
+
+pub fn query_ownership(deps: Deps) -> StdResult<OwnershipResponse> {
+    let ownership = OWNER.may_load(deps.storage)?.unwrap_or_default();
+    Ok(OwnershipResponse {
+        owner: ownership.owner.map(Into::into),
+        pending_owner: ownership.pending_owner.map(Into::into),
+        pending_expiry: ownership.pending_expiry,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::{ mock_dependencies, mock_env, mock_info };
+    use cosmwasm_std::Uint128;
+
+    use crate::contract::{ execute, instantiate };
+    use crate::msg::{ ExecuteMsg, InstantiateMsg, TransferFeeInfo };
+    use crate::pause::query_pause_info;
+
+    fn do_instantiate(deps: DepsMut) {
+        let instantiate_msg = InstantiateMsg {
+            name: "Auto Gen".to_string(),
+            symbol: "AUTO".to_string(),
+            decimals: 3,
+            owner: Some("owner".into()),
+            pause_guardian: Some("guardian".into()),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[]);
+        instantiate(deps, mock_env(), info, instantiate_msg).unwrap();
+    }
+
+    #[test]
+    fn ownership_is_handed_over_in_two_steps() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+        let mut env = mock_env();
+
+        let msg = ExecuteMsg::TransferOwnership {
+            new_owner: "new_owner".into(),
+            expires: Some(Expiration::AtHeight(env.block.height + 10)),
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("new_owner", &[]), msg.clone());
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+        // the owner stays in charge until the transfer is accepted
+        let res = query_ownership(deps.as_ref()).unwrap();
+        assert_eq!(res.owner, Some("owner".to_string()));
+        assert_eq!(res.pending_owner, Some("new_owner".to_string()));
+        let accept = ExecuteMsg::AcceptOwnership {};
+        let err = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), accept.clone());
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+
+        env.block.height += 10;
+        let err = execute(deps.as_mut(), env.clone(), mock_info("new_owner", &[]), accept.clone());
+        assert_eq!(err.unwrap_err(), ContractError::OwnershipTransferExpired {});
+
+        let msg = ExecuteMsg::TransferOwnership { new_owner: "new_owner".into(), expires: None };
+        execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+        execute(deps.as_mut(), env.clone(), mock_info("new_owner", &[]), accept).unwrap();
+        assert_eq!(query_ownership(deps.as_ref()).unwrap(), OwnershipResponse {
+            owner: Some("new_owner".to_string()),
+            pending_owner: None,
+            pending_expiry: None,
+        });
+
+        let renounce = ExecuteMsg::RenounceOwnership {};
+        let err = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), renounce.clone());
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        execute(deps.as_mut(), env, mock_info("new_owner", &[]), renounce).unwrap();
+        assert_eq!(query_ownership(deps.as_ref()).unwrap().owner, None);
+    }
+
+    #[test]
+    fn owner_can_change_configuration() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+        let owner = mock_info("owner", &[]);
+
+        let msg = ExecuteMsg::Pause { actions: None };
+        execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+        assert!(query_pause_info(deps.as_ref()).unwrap().transfers_paused);
+
+        // there is no fee admin, but the owner can still set a fee
+        let msg = ExecuteMsg::UpdateTransferFee {
+            fee: TransferFeeInfo { rate_bps: 100, max_fee: Some(Uint128::new(5)), collector: None },
+        };
+        execute(deps.as_mut(), mock_env(), owner.clone(), msg.clone()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("guardian", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let msg = ExecuteMsg::UpdateLimitExemptions { add: vec!["pair".into()], remove: vec![] };
+        execute(deps.as_mut(), mock_env(), owner, msg).unwrap();
+    }
+}
@@ -0,0 +1,211 @@
+use cosmwasm_std::{ attr, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage };
+
+use crate::error::ContractError;
+use crate::msg::{ PausableAction, PauseInfoResponse };
+use crate::roles::{ assert_primary_holder, assert_role };
+use crate::state::{ PauseState, Role, PAUSE_STATE };
+
+/// Fails with `ContractError::Paused` if the guardian has halted the given kind of action.
+pub fn assert_not_paused(
+    storage: &dyn Storage,
+    action: PausableAction
+) -> Result<(), ContractError> {
+    let state = PAUSE_STATE.may_load(storage)?.unwrap_or_default();
+    let paused = match action {
+        PausableAction::Transfer => state.transfers_paused,
+        PausableAction::Mint => state.mint_paused,
+        PausableAction::Burn => state.burn_paused,
+    };
+    if paused {
+        Err(ContractError::Paused {})
+    } else {
+        Ok(())
+    }
+}
+
+fn load_for_guardian(
+    storage: &dyn Storage,
+    info: &MessageInfo
+) -> Result<PauseState, ContractError> {
+    assert_role(storage, Role::Pauser, &info.sender)?;
+    Ok(PAUSE_STATE.may_load(storage)?.unwrap_or_default())
+}
+
+fn set_paused(state: &mut PauseState, actions: Option<Vec<PausableAction>>, paused: bool) {
+    let actions = actions.unwrap_or_else(|| {
+        vec![PausableAction::Transfer, PausableAction::Mint, PausableAction::Burn]
+    });
+    for action in actions {
+        match action {
+            PausableAction::Transfer => {
+                state.transfers_paused = paused;
+            }
+            PausableAction::Mint => {
+                state.mint_paused = paused;
+            }
+            PausableAction::Burn => {
+                state.burn_paused = paused;
+            }
+        }
+    }
+}
+
+fn pause_attributes(action: &str, state: &PauseState) -> Response {
+    Response::new().add_attributes(
+        vec![
+            attr("action", action),
+            attr("transfers_paused", state.transfers_paused.to_string()),
+            attr("mint_paused", state.mint_paused.to_string()),
+            attr("burn_paused", state.burn_paused.to_string())
+        ]
+    )
+}
+
+pub fn execute_pause(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    actions: Option<Vec<PausableAction>>
+) -> Result<Response, ContractError> {
+    let mut state = load_for_guardian(deps.storage, &info)?;
+    set_paused(&mut state, actions, true);
+    PAUSE_STATE.save(deps.storage, &state)?;
+
+    Ok(pause_attributes("pause", &state))
+}
+
+pub fn execute_unpause(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    actions: Option<Vec<PausableAction>>
+) -> Result<Response, ContractError> {
+    let mut state = load_for_guardian(deps.storage, &info)?;
+    set_paused(&mut state, actions, false);
+    PAUSE_STATE.save(deps.storage, &state)?;
+
+    Ok(pause_attributes("unpause", &state))
+}
+
+pub fn execute_update_pause_guardian(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    new_guardian: Option<String>
+) -> Result<Response, ContractError> {
+    assert_primary_holder(deps.storage, Role::Pauser, &info.sender)?;
+    let mut state = PAUSE_STATE.may_load(deps.storage)?.unwrap_or_default();
+    state.guardian = new_guardian.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+    PAUSE_STATE.save(deps.storage, &state)?;
+
+    let res = Response::new()
+        .add_attribute("action", "update_pause_guardian")
+        .add_attribute(
+            "new_guardian",
+            state.guardian.map(Into::into).unwrap_or_else(|| "None".to_string())
+        );
+    Ok(res)
+}
+
+pub fn query_pause_info(deps: Deps) -> StdResult<PauseInfoResponse> {
+    let state = PAUSE_STATE.may_load(deps.storage)?.unwrap_or_default();
+    Ok(PauseInfoResponse {
+        guardian: state.guardian.map(Into::into),
+        transfers_paused: state.transfers_paused,
+        mint_paused: state.mint_paused,
+        burn_paused: state.burn_paused,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::{ mock_dependencies, mock_env, mock_info };
+    use cosmwasm_std::{ Uint128, Binary };
+    use cw20::{ Cw20Coin, MinterResponse };
+
+    use crate::contract::{ execute, instantiate };
+    use crate::msg::{ ExecuteMsg, InstantiateMsg };
+
+    fn do_instantiate(deps: DepsMut, guardian: &str) {
+        let instantiate_msg = InstantiateMsg {
+            name: "Auto Gen".to_string(),
+            symbol: "AUTO".to_string(),
+            decimals: 3,
+            initial_balances: vec![Cw20Coin {
+                address: "owner".into(),
+                amount: Uint128::new(1000),
+            }],
+            mint: Some(MinterResponse {
+                minter: "minter".into(),
+                cap: None,
+            }),
+            pause_guardian: Some(guardian.into()),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[]);
+        instantiate(deps, mock_env(), info, instantiate_msg).unwrap();
+    }
+
+    #[test]
+    fn only_guardian_can_pause() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), "guardian");
+
+        let msg = ExecuteMsg::Pause { actions: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg.clone());
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+
+        execute(deps.as_mut(), mock_env(), mock_info("guardian", &[]), msg).unwrap();
+        let info = query_pause_info(deps.as_ref()).unwrap();
+        assert_eq!(info, PauseInfoResponse {
+            guardian: Some("guardian".to_string()),
+            transfers_paused: true,
+            mint_paused: true,
+            burn_paused: true,
+        });
+
+        // the guardian can hand over the role, after which it loses it
+        let msg = ExecuteMsg::UpdatePauseGuardian { guardian: Some("guardian2".to_string()) };
+        execute(deps.as_mut(), mock_env(), mock_info("guardian", &[]), msg).unwrap();
+        let msg = ExecuteMsg::Unpause { actions: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("guardian", &[]), msg.clone());
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("guardian2", &[]), msg).unwrap();
+        assert!(!query_pause_info(deps.as_ref()).unwrap().transfers_paused);
+    }
+
+    #[test]
+    fn granular_pause_blocks_only_selected_actions() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), "guardian");
+
+        let msg = ExecuteMsg::Pause { actions: Some(vec![PausableAction::Transfer]) };
+        execute(deps.as_mut(), mock_env(), mock_info("guardian", &[]), msg).unwrap();
+
+        let owner = mock_info("owner", &[]);
+        let msg = ExecuteMsg::Transfer { recipient: "other".into(), amount: Uint128::new(1) };
+        let err = execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
+
+        let msg = ExecuteMsg::Send {
+            contract: "contract".into(),
+            amount: Uint128::new(1),
+            msg: Binary::default(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
+
+        // burning and minting are still allowed
+        let msg = ExecuteMsg::Burn { amount: Uint128::new(1) };
+        execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+        let msg = ExecuteMsg::Mint { recipient: "owner".into(), amount: Uint128::new(1) };
+        execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg.clone()).unwrap();
+
+        let pause_mint = ExecuteMsg::Pause { actions: Some(vec![PausableAction::Mint]) };
+        execute(deps.as_mut(), mock_env(), mock_info("guardian", &[]), pause_mint).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
+    }
+}
@@ -0,0 +1,245 @@
+use cosmwasm_std::{
+    attr,
+    to_vec,
+    Addr,
+    Api,
+    Binary,
+    CanonicalAddr,
+    Deps,
+    DepsMut,
+    Env,
+    MessageInfo,
+    Response,
+    StdResult,
+    Uint128,
+};
+use cw20::Expiration;
+use ripemd::Ripemd160;
+use sha2::{ Digest, Sha256 };
+
+use crate::allowances::set_allowance;
+use crate::error::ContractError;
+use crate::msg::{ NonceResponse, PermitPayload };
+use crate::state::NONCES;
+
+/// Returns the account address controlled by a compressed secp256k1 public key, the same way
+/// the chain derives it: RIPEMD-160 of the SHA-256 of the key.
+pub fn pubkey_to_address(api: &dyn Api, pubkey: &[u8]) -> StdResult<Addr> {
+    let hash = Ripemd160::digest(Sha256::digest(pubkey));
+    api.addr_humanize(&CanonicalAddr::from(&hash[..]))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_permit(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    owner_pubkey: Binary,
+    spender: String,
+    amount: Uint128,
+    expires: Option<Expiration>,
+    nonce: u64,
+    signature: Binary
+) -> Result<Response, ContractError> {
+    let payload = PermitPayload {
+        chain_id: env.block.chain_id.clone(),
+        contract: env.contract.address.to_string(),
+        spender: spender.clone(),
+        amount,
+        expires,
+        nonce,
+    };
+    let hash = Sha256::digest(to_vec(&payload)?);
+    let valid = deps.api
+        .secp256k1_verify(&hash, &signature, &owner_pubkey)
+        .map_err(|_| ContractError::InvalidPermitSignature {})?;
+    if !valid {
+        return Err(ContractError::InvalidPermitSignature {});
+    }
+
+    let owner = pubkey_to_address(deps.api, &owner_pubkey)?;
+    let expected = NONCES.may_load(deps.storage, &owner)?.unwrap_or_default();
+    if nonce != expected {
+        return Err(ContractError::InvalidPermitNonce { expected });
+    }
+    NONCES.save(deps.storage, &owner, &(expected + 1))?;
+
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    set_allowance(deps.storage, &env.block, &owner, &spender_addr, amount, expires)?;
+
+    let res = Response::new().add_attributes(
+        vec![
+            attr("action", "permit"),
+            attr("owner", owner),
+            attr("spender", spender),
+            attr("amount", amount),
+            attr("nonce", nonce.to_string())
+        ]
+    );
+    Ok(res)
+}
+
+pub fn query_nonce(deps: Deps, owner: String) -> StdResult<NonceResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let nonce = NONCES.may_load(deps.storage, &owner)?.unwrap_or_default();
+    Ok(NonceResponse { nonce })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::marker::PhantomData;
+
+    use cosmwasm_std::testing::{ mock_env, mock_info, MockApi, MockQuerier, MockStorage };
+    use cosmwasm_std::{ OwnedDeps, RecoverPubkeyError, VerificationError };
+    use cw20::Cw20Coin;
+    use k256::ecdsa::signature::hazmat::PrehashSigner;
+    use k256::ecdsa::{ Signature, SigningKey };
+
+    use crate::allowances::query_allowance;
+    use crate::contract::{ execute, instantiate, query_balance };
+    use crate::msg::{ ExecuteMsg, InstantiateMsg };
+
+    /// `MockApi` cannot humanize 20 byte account hashes, so those are shown as hex instead.
+    #[derive(Copy, Clone, Default)]
+    struct PermitApi(MockApi);
+
+    impl Api for PermitApi {
+        fn addr_validate(&self, human: &str) -> StdResult<Addr> {
+            if human.len() == 40 {
+                return Ok(Addr::unchecked(human));
+            }
+            self.0.addr_validate(human)
+        }
+
+        fn addr_canonicalize(&self, human: &str) -> StdResult<CanonicalAddr> {
+            self.0.addr_canonicalize(human)
+        }
+
+        fn addr_humanize(&self, canonical: &CanonicalAddr) -> StdResult<Addr> {
+            if canonical.len() == 20 {
+                let hex = canonical
+                    .as_slice()
+                    .iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect::<String>();
+                return Ok(Addr::unchecked(hex));
+            }
+            self.0.addr_humanize(canonical)
+        }
+
+        fn secp256k1_verify(
+            &self,
+            message_hash: &[u8],
+            signature: &[u8],
+            public_key: &[u8]
+        ) -> Result<bool, VerificationError> {
+            self.0.secp256k1_verify(message_hash, signature, public_key)
+        }
+
+        fn secp256k1_recover_pubkey(
+            &self,
+            message_hash: &[u8],
+            signature: &[u8],
+            recovery_param: u8
+        ) -> Result<Vec<u8>, RecoverPubkeyError> {
+            self.0.secp256k1_recover_pubkey(message_hash, signature, recovery_param)
+        }
+
+        fn ed25519_verify(
+            &self,
+            message: &[u8],
+            signature: &[u8],
+            public_key: &[u8]
+        ) -> Result<bool, VerificationError> {
+            self.0.ed25519_verify(message, signature, public_key)
+        }
+
+        fn ed25519_batch_verify(
+            &self,
+            messages: &[&[u8]],
+            signatures: &[&[u8]],
+            public_keys: &[&[u8]]
+        ) -> Result<bool, VerificationError> {
+            self.0.ed25519_batch_verify(messages, signatures, public_keys)
+        }
+
+        fn debug(&self, message: &str) {
+            self.0.debug(message)
+        }
+    }
+
+    fn sign(key: &SigningKey, payload: &PermitPayload) -> Binary {
+        let hash = Sha256::digest(to_vec(payload).unwrap());
+        let signature: Signature = key.sign_prehash(&hash).unwrap();
+        Binary::from(&signature.to_bytes()[..])
+    }
+
+    #[test]
+    fn relayed_permit_sets_allowance_once() {
+        let mut deps = OwnedDeps {
+            storage: MockStorage::default(),
+            api: PermitApi::default(),
+            querier: MockQuerier::default(),
+            custom_query_type: PhantomData,
+        };
+        let key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let pubkey = Binary::from(key.verifying_key().to_encoded_point(true).as_bytes());
+        let owner = pubkey_to_address(&deps.api, &pubkey).unwrap();
+
+        let instantiate_msg = InstantiateMsg {
+            name: "Auto Gen".to_string(),
+            symbol: "AUTO".to_string(),
+            decimals: 3,
+            initial_balances: vec![Cw20Coin {
+                address: owner.to_string(),
+                amount: Uint128::new(1000),
+            }],
+            ..Default::default()
+        };
+        let env = mock_env();
+        instantiate(deps.as_mut(), env.clone(), mock_info("creator", &[]), instantiate_msg).unwrap();
+
+        let payload = PermitPayload {
+            chain_id: env.block.chain_id.clone(),
+            contract: env.contract.address.to_string(),
+            spender: "spender".into(),
+            amount: Uint128::new(300),
+            expires: None,
+            nonce: 0,
+        };
+        let permit = |payload: &PermitPayload, signature: Binary| ExecuteMsg::Permit {
+            owner_pubkey: pubkey.clone(),
+            spender: payload.spender.clone(),
+            amount: payload.amount,
+            expires: payload.expires,
+            nonce: payload.nonce,
+            signature,
+        };
+
+        // a payload signed for another chain does not verify here
+        let other_chain = PermitPayload { chain_id: "other-chain".into(), ..payload.clone() };
+        let msg = permit(&payload, sign(&key, &other_chain));
+        let err = execute(deps.as_mut(), env.clone(), mock_info("relayer", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidPermitSignature {});
+
+        let msg = permit(&payload, sign(&key, &payload));
+        execute(deps.as_mut(), env.clone(), mock_info("relayer", &[]), msg.clone()).unwrap();
+        let allowance = query_allowance(deps.as_ref(), mock_env(), owner.to_string(), "spender".into()).unwrap();
+        assert_eq!(allowance.allowance, Uint128::new(300));
+        assert_eq!(query_nonce(deps.as_ref(), owner.to_string()).unwrap().nonce, 1);
+
+        // replaying the same permit is rejected
+        let err = execute(deps.as_mut(), env.clone(), mock_info("relayer", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidPermitNonce { expected: 1 });
+
+        let msg = ExecuteMsg::TransferFrom {
+            owner: owner.to_string(),
+            recipient: "spender".into(),
+            amount: Uint128::new(300),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("spender", &[]), msg).unwrap();
+        assert_eq!(query_balance(deps.as_ref(), "spender".into()).unwrap().balance, Uint128::new(300));
+    }
+}
@@ -0,0 +1,328 @@
+use cosmwasm_std::{
+    attr,
+    Addr,
+    Deps,
+    DepsMut,
+    Empty,
+    Env,
+    MessageInfo,
+    Response,
+    StdResult,
+    Storage,
+};
+
+use crate::error::ContractError;
+use crate::msg::HasRoleResponse;
+use crate::state::{
+    Role,
+    COMPLIANCE_CONFIG,
+    FEE_CONFIG,
+    MARKETING_INFO,
+    MINT_CAP_CONFIG,
+    OWNER,
+    PAUSE_STATE,
+    ROLES,
+    ROLE_ADMINS,
+    TOKEN_INFO,
+    TRANSFER_RESTRICTION,
+};
+
+/// Returns the address holding `role` in the slot it had before roles existed, if any.
+pub fn primary_holder(storage: &dyn Storage, role: Role) -> StdResult<Option<Addr>> {
+    let holder = match role {
+        Role::Admin => OWNER.may_load(storage)?.unwrap_or_default().owner,
+        Role::Minter =>
+            TOKEN_INFO.may_load(storage)?
+                .and_then(|info| info.mint)
+                .map(|mint| mint.minter),
+        Role::Marketing => MARKETING_INFO.may_load(storage)?.and_then(|info| info.marketing),
+        Role::Pauser => PAUSE_STATE.may_load(storage)?.unwrap_or_default().guardian,
+        Role::Compliance => COMPLIANCE_CONFIG.may_load(storage)?.unwrap_or_default().admin,
+        Role::FeeAdmin => FEE_CONFIG.may_load(storage)?.unwrap_or_default().admin,
+        Role::LaunchAdmin => TRANSFER_RESTRICTION.may_load(storage)?.unwrap_or_default().admin,
+        Role::MintCapAdmin => MINT_CAP_CONFIG.may_load(storage)?.unwrap_or_default().admin,
+        Role::MintSigner | Role::Checkpointer => None,
+    };
+    Ok(holder)
+}
+
+pub fn has_role(storage: &dyn Storage, role: Role, address: &Addr) -> StdResult<bool> {
+    if ROLES.has(storage, (role.as_str(), address)) {
+        return Ok(true);
+    }
+    Ok(primary_holder(storage, role)?.as_ref() == Some(address))
+}
+
+/// Whether admins may act as holders of `role`.
+fn admin_acts_as(role: Role) -> bool {
+    matches!(role, Role::Pauser | Role::FeeAdmin | Role::LaunchAdmin | Role::Checkpointer)
+}
+
+/// Whether `address` may act in `role`: it holds the role, or it is an admin and admins act as
+/// the pauser, the fee admin, the launch admin and the checkpointer too.
+pub fn can_act_as(storage: &dyn Storage, role: Role, address: &Addr) -> StdResult<bool> {
+    Ok(
+        has_role(storage, role, address)? ||
+            (admin_acts_as(role) && has_role(storage, Role::Admin, address)?)
+    )
+}
+
+/// Fails with `ContractError::Unauthorized` unless `address` may act in `role`.
+pub fn assert_role(storage: &dyn Storage, role: Role, address: &Addr) -> Result<(), ContractError> {
+    if can_act_as(storage, role, address)? {
+        return Ok(());
+    }
+    Err(ContractError::Unauthorized {})
+}
+
+/// Fails with `ContractError::Unauthorized` unless `address` holds `role` in its primary slot,
+/// or is the owner for a role admins act in. Only they may hand that slot over or clear it, so
+/// addresses that were merely granted the role cannot take it over.
+pub fn assert_primary_holder(
+    storage: &dyn Storage,
+    role: Role,
+    address: &Addr
+) -> Result<(), ContractError> {
+    let is_holder = |role| -> StdResult<bool> {
+        Ok(primary_holder(storage, role)?.as_ref() == Some(address))
+    };
+    if is_holder(role)? || (admin_acts_as(role) && is_holder(Role::Admin)?) {
+        return Ok(());
+    }
+    Err(ContractError::Unauthorized {})
+}
+
+/// Returns the role whose holders grant and revoke `role`.
+pub fn role_admin(storage: &dyn Storage, role: Role) -> StdResult<Role> {
+    Ok(ROLE_ADMINS.may_load(storage, role.as_str())?.unwrap_or(Role::Admin))
+}
+
+pub fn execute_grant_role(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    role: Role,
+    address: String
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, role_admin(deps.storage, role)?, &info.sender)?;
+    let addr = deps.api.addr_validate(&address)?;
+    ROLES.save(deps.storage, (role.as_str(), &addr), &Empty {})?;
+
+    let res = Response::new().add_attributes(
+        vec![attr("action", "grant_role"), attr("role", role.as_str()), attr("address", address)]
+    );
+    Ok(res)
+}
+
+pub fn execute_revoke_role(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    role: Role,
+    address: String
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, role_admin(deps.storage, role)?, &info.sender)?;
+    let addr = deps.api.addr_validate(&address)?;
+    if !ROLES.has(deps.storage, (role.as_str(), &addr)) {
+        return Err(ContractError::RoleNotGranted {});
+    }
+    ROLES.remove(deps.storage, (role.as_str(), &addr));
+
+    let res = Response::new().add_attributes(
+        vec![attr("action", "revoke_role"), attr("role", role.as_str()), attr("address", address)]
+    );
+    Ok(res)
+}
+
+pub fn execute_renounce_role(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    role: Role
+) -> Result<Response, ContractError> {
+    if !ROLES.has(deps.storage, (role.as_str(), &info.sender)) {
+        return Err(ContractError::RoleNotGranted {});
+    }
+    ROLES.remove(deps.storage, (role.as_str(), &info.sender));
+
+    let res = Response::new().add_attributes(
+        vec![
+            attr("action", "renounce_role"),
+            attr("role", role.as_str()),
+            attr("address", info.sender)
+        ]
+    );
+    Ok(res)
+}
+
+pub fn execute_set_role_admin(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    role: Role,
+    admin_role: Role
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, role_admin(deps.storage, role)?, &info.sender)?;
+    ROLE_ADMINS.save(deps.storage, role.as_str(), &admin_role)?;
+
+    let res = Response::new().add_attributes(
+        vec![
+            attr("action", "set_role_admin"),
+            attr("role", role.as_str()),
+            attr("admin_role", admin_role.as_str())
+        ]
+    );
+    Ok(res)
+}
+
+pub fn query_has_role(deps: Deps, role: Role, address: String) -> StdResult<HasRoleResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    Ok(HasRoleResponse { has_role: can_act_as(deps.storage, role, &address)? })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::{ mock_dependencies, mock_env, mock_info };
+    use cosmwasm_std::Uint128;
+    use cw20::MinterResponse;
+
+    use crate::contract::{ execute, instantiate, query_balance };
+    use crate::enumerable::query_role_members;
+    use crate::msg::{ ExecuteMsg, InstantiateMsg };
+
+    fn do_instantiate(deps: DepsMut) {
+        let instantiate_msg = InstantiateMsg {
+            name: "Auto Gen".to_string(),
+            symbol: "AUTO".to_string(),
+            decimals: 3,
+            mint: Some(MinterResponse {
+                minter: "minter".into(),
+                cap: None,
+            }),
+            owner: Some("owner".into()),
+            pause_guardian: Some("guardian".into()),
+            compliance_admin: Some("compliance".into()),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[]);
+        instantiate(deps, mock_env(), info, instantiate_msg).unwrap();
+    }
+
+    #[test]
+    fn granted_roles_act_alongside_primary_holders() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+
+        let grant = ExecuteMsg::GrantRole { role: Role::Minter, address: "bridge".into() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), grant.clone());
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), grant).unwrap();
+
+        let msg = ExecuteMsg::Mint { recipient: "user".into(), amount: Uint128::new(100) };
+        execute(deps.as_mut(), mock_env(), mock_info("bridge", &[]), msg.clone()).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg.clone()).unwrap();
+        assert_eq!(query_balance(deps.as_ref(), "user".into()).unwrap().balance, Uint128::new(200));
+
+        let res = query_role_members(deps.as_ref(), Role::Minter, None, None).unwrap();
+        assert_eq!(res.primary, Some("minter".to_string()));
+        assert_eq!(res.members, vec!["bridge".to_string()]);
+        assert_eq!(res.admin_role, Role::Admin);
+
+        let revoke = ExecuteMsg::RevokeRole { role: Role::Minter, address: "bridge".into() };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), revoke.clone()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bridge", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), revoke).unwrap_err();
+        assert_eq!(err, ContractError::RoleNotGranted {});
+
+        assert!(query_has_role(deps.as_ref(), Role::Pauser, "guardian".into()).unwrap().has_role);
+        // admins may act as the pauser, so the query reports it like the check does
+        assert!(query_has_role(deps.as_ref(), Role::Pauser, "owner".into()).unwrap().has_role);
+        assert!(!query_has_role(deps.as_ref(), Role::Pauser, "minter".into()).unwrap().has_role);
+        assert!(!query_has_role(deps.as_ref(), Role::Compliance, "owner".into()).unwrap().has_role);
+    }
+
+    #[test]
+    fn role_admins_form_a_hierarchy() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+        let owner = mock_info("owner", &[]);
+
+        // the pause guardian takes over managing pausers from the admins
+        let msg = ExecuteMsg::SetRoleAdmin { role: Role::Pauser, admin_role: Role::Pauser };
+        execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+        let grant = ExecuteMsg::GrantRole { role: Role::Pauser, address: "bot".into() };
+        execute(deps.as_mut(), mock_env(), mock_info("guardian", &[]), grant).unwrap();
+
+        let msg = ExecuteMsg::Pause { actions: None };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap();
+
+        let renounce = ExecuteMsg::RenounceRole { role: Role::Pauser };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), renounce.clone()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), renounce).unwrap_err();
+        assert_eq!(err, ContractError::RoleNotGranted {});
+        let msg = ExecuteMsg::Unpause { actions: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // granting roles administered by the admin role needs an admin
+        let grant = ExecuteMsg::GrantRole { role: Role::Compliance, address: "bot".into() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("guardian", &[]), grant.clone());
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), owner, grant).unwrap();
+        let msg = ExecuteMsg::Freeze { address: "user".into(), reason: "stolen funds".into() };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap();
+    }
+
+    #[test]
+    fn grantees_cannot_take_over_primary_slots() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+        let owner = mock_info("owner", &[]);
+        for (role, address) in [
+            (Role::Admin, "co_admin"),
+            (Role::Pauser, "bot"),
+            (Role::Compliance, "officer"),
+            (Role::Minter, "bridge"),
+        ] {
+            let grant = ExecuteMsg::GrantRole { role, address: address.into() };
+            execute(deps.as_mut(), mock_env(), owner.clone(), grant).unwrap();
+        }
+
+        // grantees act in the role, but cannot hand its primary slot over
+        let msg = ExecuteMsg::Pause { actions: None };
+        execute(deps.as_mut(), mock_env(), mock_info("bot", &[]), msg).unwrap();
+        let msg = ExecuteMsg::UpdatePauseGuardian { guardian: Some("bot".into()) };
+        for sender in ["bot", "co_admin"] {
+            let err = execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg.clone());
+            assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        }
+        let msg = ExecuteMsg::UpdateComplianceAdmin { admin: Some("officer".into()) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("officer", &[]), msg);
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+
+        let bridge = mock_info("bridge", &[]);
+        let msg = ExecuteMsg::ProposeMinter { new_minter: "bridge".into(), expires: None };
+        let err = execute(deps.as_mut(), mock_env(), bridge.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let msg = ExecuteMsg::UpdateMinter { new_minter: None };
+        let err = execute(deps.as_mut(), mock_env(), bridge.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let msg = ExecuteMsg::SetMinter {
+            minter: "other".into(),
+            cap: None,
+            rate_limit: None,
+            expires: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), bridge, msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // the owner still replaces the pause guardian, as before roles existed
+        let msg = ExecuteMsg::UpdatePauseGuardian { guardian: Some("new_guardian".into()) };
+        execute(deps.as_mut(), mock_env(), owner, msg).unwrap();
+        let res = query_role_members(deps.as_ref(), Role::Pauser, None, None).unwrap();
+        assert_eq!(res.primary, Some("new_guardian".to_string()));
+    }
+}
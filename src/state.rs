@@ -1,8 +1,21 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::{Item, Map, SnapshotMap, Strategy};
+use cosmwasm_std::{Addr, Empty, Order, StdError, StdResult, Storage, Uint128};
+use cw_storage_plus::{
+    Bound,
+    Index,
+    IndexList,
+    IndexedSnapshotMap,
+    Item,
+    KeyDeserialize,
+    Map,
+    MultiIndex,
+    SnapshotItem,
+    SnapshotMap,
+    Strategy,
+};
 
-use cw20::{AllowanceResponse, Logo, MarketingInfoResponse};
+use cw20::{AllowanceResponse, Expiration, Logo, MarketingInfoResponse};
+use cw_utils::Duration;
 
 #[cw_serde]
 pub struct TokenInfo {
@@ -20,25 +33,584 @@ pub struct MinterData {
     pub cap: Option<Uint128>,
 }
 
+#[cw_serde]
+pub struct MintRateLimit {
+    /// The most tokens that can be minted within a single period
+    pub amount: Uint128,
+    /// Length of a period, in blocks
+    pub period: u64,
+}
+
+/// Minting rights delegated by the minter in `MinterData` to another address
+#[cw_serde]
+pub struct MinterAllowance {
+    /// How many tokens this minter may issue in total. None means only the token cap applies
+    pub cap: Option<Uint128>,
+    /// How many tokens this minter has issued so far
+    pub minted: Uint128,
+    pub rate_limit: Option<MintRateLimit>,
+    /// First block of the current rate limit period
+    pub period_start: u64,
+    /// Tokens issued since `period_start`
+    pub period_minted: Uint128,
+    pub expires: Expiration,
+}
+
 impl TokenInfo {
     pub fn get_cap(&self) -> Option<Uint128> {
         self.mint.as_ref().and_then(|v| v.cap)
     }
 }
 
+#[cw_serde]
+#[derive(Default)]
+pub struct MintCapConfig {
+    /// An address besides the minter allowed to change the cap
+    pub admin: Option<Addr>,
+    /// The highest the cap can be raised to. Lowering the cap lowers this as well
+    pub ceiling: Option<Uint128>,
+}
+
+/// A minter handover waiting for the proposed address to accept it
+#[cw_serde]
+pub struct PendingMinter {
+    pub new_minter: Addr,
+    pub expires: Expiration,
+}
+
 pub const TOKEN_INFO: Item<TokenInfo> = Item::new("token_info");
+pub const PENDING_MINTER: Item<PendingMinter> = Item::new("pending_minter");
+pub const MINT_CAP_CONFIG: Item<MintCapConfig> = Item::new("mint_cap_config");
+pub const MINTERS: Map<&Addr, MinterAllowance> = Map::new("minters");
+
+/// How many mint signers have to approve a mint proposal before it is executed
+#[cw_serde]
+pub struct MintApprovalConfig {
+    pub threshold: u32,
+    /// How long a proposal stays open for approvals
+    pub proposal_duration: Duration,
+}
+
+/// A mint waiting for enough mint signers to approve it
+#[cw_serde]
+pub struct MintProposal {
+    pub proposer: Addr,
+    pub recipient: Addr,
+    pub amount: Uint128,
+    pub memo: Option<String>,
+    /// Signers that approved the proposal, the proposer included
+    pub approvals: Vec<Addr>,
+    pub expires: Expiration,
+    pub executed: bool,
+}
+
+/// Mint approvals are disabled when unset
+pub const MINT_APPROVAL_CONFIG: Item<MintApprovalConfig> = Item::new("mint_approval_config");
+pub const MINT_PROPOSALS: Map<u64, MintProposal> = Map::new("mint_proposals");
+pub const NEXT_MINT_PROPOSAL_ID: Item<u64> = Item::new("next_mint_proposal_id");
+
+/// How a vested amount unlocks over time. All times are in seconds since the epoch
+#[cw_serde]
+pub enum VestingSchedule {
+    /// The whole amount unlocks at `release_at`
+    Cliff {
+        amount: Uint128,
+        release_at: u64,
+    },
+    /// The amount unlocks linearly from `start` to `end`, but nothing is released before
+    /// `cliff` if one is set
+    Linear {
+        amount: Uint128,
+        start: u64,
+        end: u64,
+        cliff: Option<u64>,
+    },
+    /// An equal share of the amount unlocks every `interval` seconds after `start`,
+    /// `steps` times in total
+    Step {
+        amount: Uint128,
+        start: u64,
+        interval: u64,
+        steps: u32,
+    },
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct FeeConfig {
+    /// The address allowed to change the fee schedule and the exemption list
+    pub admin: Option<Addr>,
+    /// Fee charged on every transfer, in basis points of the transferred amount
+    pub rate_bps: u16,
+    /// Upper bound on the fee charged for a single transfer
+    pub max_fee: Option<Uint128>,
+    /// Receives the collected fees. Fees are burned if this is None
+    pub collector: Option<Addr>,
+}
+
+pub const VESTING: Map<&Addr, VestingSchedule> = Map::new("vesting");
+pub const FEE_CONFIG: Item<FeeConfig> = Item::new("fee_config");
+/// Addresses (DEX pairs, the router...) whose incoming and outgoing transfers are never charged
+pub const FEE_EXEMPT: Map<&Addr, Empty> = Map::new("fee_exempt");
 pub const MARKETING_INFO: Item<MarketingInfoResponse> = Item::new("marketing_info");
 pub const LOGO: Item<Logo> = Item::new("logo");
-pub const ALLOWANCES: Map<(&Addr, &Addr), AllowanceResponse> = Map::new("allowance");
-// TODO: After https://github.com/CosmWasm/cw-plus/issues/670 is implemented, replace this with a `MultiIndex` over `ALLOWANCES`
-pub const ALLOWANCES_SPENDER: Map<(&Addr, &Addr), AllowanceResponse> =
+
+/// The privileged roles. Each has a primary holder kept where it was before roles existed
+/// (the cw20 minter, the marketing address, the pause guardian...), and any number of
+/// addresses can be granted the role on top of that.
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum Role {
+    /// Grants and revokes every other role by default, and can act as the pauser, fee admin,
+    /// launch admin and checkpointer. The owner is its primary holder
+    Admin,
+    Minter,
+    Marketing,
+    Pauser,
+    Compliance,
+    FeeAdmin,
+    LaunchAdmin,
+    MintCapAdmin,
+    /// Proposes and approves mints that need several signers. Has no primary holder
+    MintSigner,
+    /// Marks heights to record balances at with the Selected snapshot strategy. Has no
+    /// primary holder
+    Checkpointer,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Minter => "minter",
+            Role::Marketing => "marketing",
+            Role::Pauser => "pauser",
+            Role::Compliance => "compliance",
+            Role::FeeAdmin => "fee_admin",
+            Role::LaunchAdmin => "launch_admin",
+            Role::MintCapAdmin => "mint_cap_admin",
+            Role::MintSigner => "mint_signer",
+            Role::Checkpointer => "checkpointer",
+        }
+    }
+}
+
+/// Addresses granted a role, keyed by `(role, address)`
+pub const ROLES: Map<(&str, &Addr), Empty> = Map::new("roles");
+/// The role whose holders grant and revoke a role. Admin when unset
+pub const ROLE_ADMINS: Map<&str, Role> = Map::new("role_admins");
+
+/// A privileged change that has to wait out the timelock delay once one is set
+#[cw_serde]
+pub enum TimelockOperation {
+    ProposeMinter {
+        new_minter: String,
+        expires: Option<Expiration>,
+    },
+    SetMinter {
+        minter: String,
+        cap: Option<Uint128>,
+        rate_limit: Option<MintRateLimit>,
+        expires: Option<Expiration>,
+    },
+    UpdateMintCap {
+        cap: Uint128,
+    },
+    GrantRole {
+        role: Role,
+        address: String,
+    },
+    SetRoleAdmin {
+        role: Role,
+        admin_role: Role,
+    },
+    UpdateTimelockDelay {
+        delay: Option<Duration>,
+    },
+    UpdateMintApproval {
+        config: Option<MintApprovalConfig>,
+    },
+    UpdateMinter {
+        new_minter: Option<String>,
+    },
+    UpdateMarketing {
+        project: Option<String>,
+        description: Option<String>,
+        marketing: Option<String>,
+    },
+    UpdatePauseGuardian {
+        guardian: Option<String>,
+    },
+    UpdateComplianceAdmin {
+        admin: Option<String>,
+    },
+    TransferOwnership {
+        new_owner: String,
+        expires: Option<Expiration>,
+    },
+    AcceptOwnership {},
+    RenounceOwnership {},
+}
+
+#[cw_serde]
+pub struct ScheduledOperation {
+    pub operation: TimelockOperation,
+    /// The address that scheduled the operation. It is executed with its privileges
+    pub proposer: Addr,
+    /// The operation can be executed once this has passed
+    pub eta: Expiration,
+}
+
+/// How long privileged changes wait between being scheduled and taking effect. Those changes
+/// apply immediately when unset
+pub const TIMELOCK_DELAY: Item<Duration> = Item::new("timelock_delay");
+pub const OPERATIONS: Map<u64, ScheduledOperation> = Map::new("timelock_operations");
+pub const NEXT_OPERATION_ID: Item<u64> = Item::new("next_timelock_operation_id");
+
+/// The contract owner and an ownership transfer waiting to be accepted
+#[cw_serde]
+#[derive(Default)]
+pub struct Ownership {
+    /// The address allowed to change the token's configuration. None once renounced
+    pub owner: Option<Addr>,
+    pub pending_owner: Option<Addr>,
+    pub pending_expiry: Option<Expiration>,
+}
+
+pub const OWNER: Item<Ownership> = Item::new("ownership");
+
+#[cw_serde]
+#[derive(Default)]
+pub struct PauseState {
+    /// The address allowed to pause and unpause the token
+    pub guardian: Option<Addr>,
+    pub transfers_paused: bool,
+    pub mint_paused: bool,
+    pub burn_paused: bool,
+}
+
+pub const PAUSE_STATE: Item<PauseState> = Item::new("pause_state");
+
+#[cw_serde]
+#[derive(Default)]
+pub struct ComplianceConfig {
+    /// The address allowed to freeze and unfreeze accounts
+    pub admin: Option<Addr>,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct TransferRestriction {
+    /// The address allowed to manage the allowlist and open trading
+    pub admin: Option<Addr>,
+    /// While set, tokens only move between allowlisted addresses. Cleared for good by
+    /// `ExecuteMsg::OpenTrading`
+    pub allowlist_only: bool,
+}
+
+pub const TRANSFER_RESTRICTION: Item<TransferRestriction> = Item::new("transfer_restriction");
+/// Addresses (team, LP seeding contract...) that may move tokens before trading opens
+pub const TRANSFER_ALLOWLIST: Map<&Addr, Empty> = Map::new("transfer_allowlist");
+
+/// Anti-whale limits on what a single transfer or mint may credit to one account
+#[cw_serde]
+#[derive(Default)]
+pub struct LaunchLimits {
+    /// The most a single transfer or mint may credit
+    pub max_tx_amount: Option<Uint128>,
+    /// The most any account may hold after being credited
+    pub max_wallet_balance: Option<Uint128>,
+}
+
+pub const LAUNCH_LIMITS: Item<LaunchLimits> = Item::new("launch_limits");
+/// Addresses (DEX pairs, the fee collector...) the launch limits do not apply to
+pub const LIMIT_EXEMPT: Map<&Addr, Empty> = Map::new("limit_exempt");
+
+pub const COMPLIANCE_CONFIG: Item<ComplianceConfig> = Item::new("compliance_config");
+/// Accounts that can neither send nor receive tokens, with the reason they were frozen for
+pub const FROZEN: Map<&Addr, String> = Map::new("frozen");
+
+pub struct AllowanceIndexes<'a> {
+    /// Allowances granted to a spender, keyed by the spender half of the `(owner, spender)` key
+    pub spender: MultiIndex<'a, Addr, AllowanceResponse, (Addr, Addr)>,
+}
+
+impl<'a> IndexList<AllowanceResponse> for AllowanceIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<AllowanceResponse>> + '_> {
+        let v: Vec<&dyn Index<AllowanceResponse>> = vec![&self.spender];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Allowances keyed by `(owner, spender)`, with a secondary index by spender and a changelog
+/// for historical queries.
+pub fn allowances<'a>() -> IndexedSnapshotMap<
+    'a,
+    (&'a Addr, &'a Addr),
+    AllowanceResponse,
+    AllowanceIndexes<'a>
+> {
+    let indexes = AllowanceIndexes {
+        spender: MultiIndex::new(
+            |pk, _| {
+                let (_, spender) = <(Addr, Addr)>::from_slice(pk).expect("invalid allowance key");
+                spender
+            },
+            "allowance",
+            "allowance__spender"
+        ),
+    };
+    IndexedSnapshotMap::new(
+        "allowance",
+        "allowance__checkpoints",
+        "allowance__changelog",
+        Strategy::EveryBlock,
+        indexes
+    )
+}
+
+/// The last spender scanned by `ExecuteMsg::PruneExpiredAllowances` for each owner, while a pass
+/// is unfinished
+pub const ALLOWANCE_PRUNE_CURSORS: Map<&Addr, Addr> = Map::new("allowance_prune_cursors");
+
+/// The `(spender, owner)` map that mirrored `allowances()` before the spender index existed.
+/// Only read by the migration that deletes it.
+pub const LEGACY_ALLOWANCES_SPENDER: Map<(&Addr, &Addr), AllowanceResponse> =
     Map::new("allowance_spender");
 
-pub const BALANCES: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
-    "balance",
-    "balance__checkpoints",
-    "balance__changelog",
+/// How balance changes are recorded for `QueryMsg::BalanceAt`
+#[cw_serde]
+#[derive(Copy, Default)]
+pub enum SnapshotStrategy {
+    /// Every change is recorded, so balances can be queried at any height
+    #[default]
+    EveryBlock,
+    /// Changes are only recorded for heights marked with `ExecuteMsg::Checkpoint`
+    Selected,
+    /// No history is kept
+    Never,
+}
+
+impl From<SnapshotStrategy> for Strategy {
+    fn from(strategy: SnapshotStrategy) -> Self {
+        match strategy {
+            SnapshotStrategy::EveryBlock => Strategy::EveryBlock,
+            SnapshotStrategy::Selected => Strategy::Selected,
+            SnapshotStrategy::Never => Strategy::Never,
+        }
+    }
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct HistoryConfig {
+    pub balance_strategy: SnapshotStrategy,
+    /// How many blocks of history are kept before anyone can prune it. None keeps it forever
+    pub retention: Option<u64>,
+}
+
+pub const HISTORY_CONFIG: Item<HistoryConfig> = Item::new("history_config");
+/// Balance history before this height was pruned and can no longer be queried
+pub const BALANCES_PRUNED_BEFORE: Item<u64> = Item::new("balances_pruned_before");
+/// The last changelog entry scanned by `ExecuteMsg::PruneBalanceHistory`, while a pass is
+/// unfinished
+pub const BALANCE_PRUNE_CURSOR: Item<(Addr, u64)> = Item::new("balance_prune_cursor");
+
+/// Balances recording their history with the given strategy. They all share the same storage
+pub const fn balances_snapshot(strategy: Strategy) -> SnapshotMap<'static, &'static Addr, Uint128> {
+    SnapshotMap::new("balance", "balance__checkpoints", "balance__changelog", strategy)
+}
+
+/// Use this for current balances only. Writes and historical reads go through
+/// `history::balances`, which applies the configured strategy
+pub const BALANCES: SnapshotMap<&Addr, Uint128> = balances_snapshot(Strategy::EveryBlock);
+
+/// The total supply at the end of a block, with what was minted and burned during it
+#[cw_serde]
+#[derive(Default)]
+pub struct SupplyRecord {
+    pub total_supply: Uint128,
+    pub minted: Uint128,
+    pub burned: Uint128,
+}
+
+/// Total supply history with a record for every block the supply changed in
+pub struct SupplyHistory<'a> {
+    records: Map<'a, u64, SupplyRecord>,
+    /// History before this height may have been pruned and can no longer be queried
+    pruned_before: Item<'a, u64>,
+}
+
+impl<'a> SupplyHistory<'a> {
+    pub const fn new(records: &'a str, pruned_before: &'a str) -> Self {
+        SupplyHistory {
+            records: Map::new(records),
+            pruned_before: Item::new(pruned_before),
+        }
+    }
+
+    pub fn records(&self) -> &Map<'a, u64, SupplyRecord> {
+        &self.records
+    }
+
+    pub fn pruned_before(&self, storage: &dyn Storage) -> StdResult<u64> {
+        Ok(self.pruned_before.may_load(storage)?.unwrap_or_default())
+    }
+
+    pub fn record_mint(
+        &self,
+        storage: &mut dyn Storage,
+        height: u64,
+        total_supply: Uint128,
+        amount: Uint128
+    ) -> StdResult<()> {
+        self.record(storage, height, total_supply, |record| {
+            record.minted += amount;
+        })
+    }
+
+    pub fn record_burn(
+        &self,
+        storage: &mut dyn Storage,
+        height: u64,
+        total_supply: Uint128,
+        amount: Uint128
+    ) -> StdResult<()> {
+        self.record(storage, height, total_supply, |record| {
+            record.burned += amount;
+        })
+    }
+
+    fn record(
+        &self,
+        storage: &mut dyn Storage,
+        height: u64,
+        total_supply: Uint128,
+        change: impl FnOnce(&mut SupplyRecord)
+    ) -> StdResult<()> {
+        let mut record = self.records.may_load(storage, height)?.unwrap_or_default();
+        record.total_supply = total_supply;
+        change(&mut record);
+        self.records.save(storage, height, &record)
+    }
+
+    /// The total supply at the end of block `height`.
+    pub fn at(&self, storage: &dyn Storage, height: u64) -> StdResult<Uint128> {
+        let pruned_before = self.pruned_before(storage)?;
+        if height < pruned_before {
+            return Err(
+                StdError::generic_err(
+                    format!("Supply history before height {} has been pruned", pruned_before)
+                )
+            );
+        }
+        let end = Some(Bound::inclusive(height));
+        let latest = self.records
+            .range(storage, None, end, Order::Descending)
+            .next()
+            .transpose()?
+            .map(|(height, record)| (height, record.total_supply));
+        // older history stays in the legacy map until the migration has moved it over
+        let legacy = LEGACY_TOTAL_SUPPLY_HISTORY.range(
+            storage,
+            None,
+            Some(Bound::inclusive(height)),
+            Order::Descending
+        )
+            .next()
+            .transpose()?;
+
+        let supply = latest
+            .into_iter()
+            .chain(legacy)
+            .max_by_key(|(height, _)| *height)
+            .map(|(_, total_supply)| total_supply);
+        Ok(supply.unwrap_or_default())
+    }
+
+    /// Drops up to `limit` records before `before_height`, except the last of them since it
+    /// still holds the supply at `before_height`. Returns how many records were dropped.
+    pub fn prune(
+        &self,
+        storage: &mut dyn Storage,
+        before_height: u64,
+        limit: usize
+    ) -> StdResult<usize> {
+        let pruned_before = self.pruned_before(storage)?.max(before_height);
+        self.pruned_before.save(storage, &pruned_before)?;
+
+        let end = Some(Bound::exclusive(pruned_before));
+        let baseline = self.records.keys(storage, None, end, Order::Descending).next().transpose()?;
+        let heights = match baseline {
+            Some(baseline) => {
+                let end = Some(Bound::exclusive(baseline));
+                self.records
+                    .keys(storage, None, end, Order::Ascending)
+                    .take(limit)
+                    .collect::<StdResult<Vec<_>>>()?
+            }
+            None => vec![],
+        };
+        for height in &heights {
+            self.records.remove(storage, *height);
+        }
+        Ok(heights.len())
+    }
+}
+
+pub const SUPPLY_HISTORY: SupplyHistory = SupplyHistory::new(
+    "supply_history",
+    "supply_pruned_before"
+);
+/// Total supply per height as recorded before 0.3.0, moved into `SUPPLY_HISTORY` by migration
+pub const LEGACY_TOTAL_SUPPLY_HISTORY: Map<u64, Uint128> = Map::new("total_supply_history");
+
+/// The next permit nonce each owner must sign
+pub const NONCES: Map<&Addr, u64> = Map::new("nonces");
+
+/// The address each delegator gave its votes to
+pub const DELEGATES: Map<&Addr, Addr> = Map::new("delegates");
+
+/// Votes delegated to each address, i.e. the summed balances of its delegators
+pub const VOTING_POWER: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "voting_power",
+    "voting_power__checkpoints",
+    "voting_power__changelog",
     Strategy::EveryBlock,
 );
 
-pub const TOTAL_SUPPLY_HISTORY: Map<u64, Uint128> = Map::new("total_supply_history");
+/// Sum of all delegated balances
+pub const TOTAL_VOTING_POWER: SnapshotItem<Uint128> = SnapshotItem::new(
+    "total_voting_power",
+    "total_voting_power__checkpoints",
+    "total_voting_power__changelog",
+    Strategy::EveryBlock,
+);
+
+/// Number of accounts with a non-zero balance
+pub const HOLDER_COUNT: SnapshotItem<u64> = SnapshotItem::new(
+    "holder_count",
+    "holder_count__checkpoints",
+    "holder_count__changelog",
+    Strategy::EveryBlock,
+);
+
+/// A unit of paged state migration work, scheduled by `migrate` and driven by
+/// `ExecuteMsg::ContinueMigration` until the queue is empty.
+#[cw_serde]
+pub enum MigrationStep {
+    /// Re-save every allowance so the spender index gets written, resuming after `start_after`
+    AllowanceIndexBackfill {
+        start_after: Option<(Addr, Addr)>,
+    },
+    /// Delete the entries of `LEGACY_ALLOWANCES_SPENDER`
+    LegacyAllowanceCleanup,
+    /// Count the accounts with a balance into `HOLDER_COUNT`, resuming after `start_after`
+    HolderCountBackfill {
+        start_after: Option<Addr>,
+    },
+    /// Move the entries of `LEGACY_TOTAL_SUPPLY_HISTORY` into `SUPPLY_HISTORY`
+    SupplyHistoryBackfill,
+}
+
+pub const PENDING_MIGRATIONS: Item<Vec<MigrationStep>> = Item::new("pending_migrations");
@@ -1,7 +1,4 @@
-use classic_terraport::{
-    mock_querier::mock_dependencies,
-    token::{ InstantiateMsg, InstantiateMarketingInfo },
-};
+use classic_terraport::mock_querier::mock_dependencies;
 use cosmwasm_std::{
     Addr,
     Uint128,
@@ -13,7 +10,11 @@ use cosmwasm_std::{
 };
 use cw20::{ MinterResponse, BalanceResponse };
 
-use crate::{ contract::{ instantiate, query, execute }, msg::QueryMsg, error::ContractError };
+use crate::{
+    contract::{ instantiate, query, execute, migrate },
+    msg::{ QueryMsg, MigrateMsg, InstantiateMsg, InstantiateMarketingInfo },
+    error::ContractError,
+};
 
 #[test]
 fn proper_initialization() {
@@ -37,6 +38,7 @@ fn proper_initialization() {
             marketing: Some("mark".to_string()),
             logo: Some(cw20::Logo::Url("".to_string())),
         }),
+        ..Default::default()
     };
 
     let info = mock_info(governance.as_str(), &[]);
@@ -79,6 +81,7 @@ fn mint_with_different_minter() {
             marketing: Some("mark".to_string()),
             logo: Some(cw20::Logo::Url("".to_string())),
         }),
+        ..Default::default()
     };
 
     let info = mock_info("user", &[]);
@@ -116,6 +119,7 @@ fn transfer_with_insufficient_balance() {
             marketing: Some("mark".to_string()),
             logo: Some(cw20::Logo::Url("".to_string())),
         }),
+        ..Default::default()
     };
 
     let info = mock_info(governance.as_str(), &[]);
@@ -184,6 +188,7 @@ fn transfer() {
             marketing: Some("mark".to_string()),
             logo: Some(cw20::Logo::Url("".to_string())),
         }),
+        ..Default::default()
     };
 
     let info = mock_info(governance.as_str(), &[]);
@@ -247,6 +252,7 @@ fn send_with_insufficient_balance() {
             marketing: Some("mark".to_string()),
             logo: Some(cw20::Logo::Url("".to_string())),
         }),
+        ..Default::default()
     };
 
     let info = mock_info(governance.as_str(), &[]);
@@ -311,6 +317,7 @@ fn send() {
             marketing: Some("mark".to_string()),
             logo: Some(cw20::Logo::Url("".to_string())),
         }),
+        ..Default::default()
     };
 
     let info = mock_info(governance.as_str(), &[]);
@@ -370,6 +377,7 @@ fn burn() {
             marketing: Some("mark".to_string()),
             logo: Some(cw20::Logo::Url("".to_string())),
         }),
+        ..Default::default()
     };
 
     let info = mock_info(governance.as_str(), &[]);
@@ -421,6 +429,7 @@ fn update_marketing_with_wrong_sender() {
             marketing: Some(governance.clone().into_string()),
             logo: Some(cw20::Logo::Url("".to_string())),
         }),
+        ..Default::default()
     };
 
     let info = mock_info(governance.as_str(), &[]);
@@ -476,6 +485,7 @@ fn update_marketing() {
             marketing: Some(governance.clone().into_string()),
             logo: Some(cw20::Logo::Url("".to_string())),
         }),
+        ..Default::default()
     };
 
     let info = mock_info(governance.as_str(), &[]);
@@ -529,6 +539,7 @@ fn update_logo_with_wrong_sender() {
             marketing: Some(governance.clone().into_string()),
             logo: Some(cw20::Logo::Url("".to_string())),
         }),
+        ..Default::default()
     };
 
     let info = mock_info(governance.as_str(), &[]);
@@ -580,6 +591,7 @@ fn update_logo() {
             marketing: Some(governance.clone().into_string()),
             logo: Some(cw20::Logo::Url("".to_string())),
         }),
+        ..Default::default()
     };
 
     let info = mock_info(governance.as_str(), &[]);
@@ -629,6 +641,7 @@ fn update_minter_with_wrong_sender() {
             marketing: Some(governance.clone().into_string()),
             logo: Some(cw20::Logo::Url("".to_string())),
         }),
+        ..Default::default()
     };
 
     let info = mock_info(governance.as_str(), &[]);
@@ -680,6 +693,7 @@ fn update_minter() {
             marketing: Some(governance.clone().into_string()),
             logo: Some(cw20::Logo::Url("".to_string())),
         }),
+        ..Default::default()
     };
 
     let info = mock_info(governance.as_str(), &[]);
@@ -699,10 +713,611 @@ fn update_minter() {
 
     let info = mock_info(governance.as_str(), &[]);
 
+    let err = execute(
+        deps.as_mut().into_empty(),
+        mock_env(),
+        info.clone(),
+        crate::msg::ExecuteMsg::UpdateMinter { new_minter: Some("user".to_string()) }
+    ).unwrap_err();
+
+    assert_eq!(err, ContractError::MinterHandoverRequired {});
+
+    let _res = execute(
+        deps.as_mut().into_empty(),
+        mock_env(),
+        info,
+        crate::msg::ExecuteMsg::ProposeMinter { new_minter: "user".to_string(), expires: None }
+    ).unwrap();
+
+    let _res = execute(
+        deps.as_mut().into_empty(),
+        mock_env(),
+        mock_info("user", &[]),
+        crate::msg::ExecuteMsg::AcceptMinter {}
+    ).unwrap();
+
+    let res = query(deps.as_ref().into_empty(), mock_env(), QueryMsg::Minter {}).unwrap();
+    let minter: Option<MinterResponse> = from_binary(&res).unwrap();
+    assert_eq!(minter.unwrap().minter, "user");
+}
+
+#[test]
+fn renounce_minter() {
+    let mut deps = mock_dependencies(&[]);
+
+    let governance = Addr::unchecked("governance");
+
+    let msg = InstantiateMsg {
+        name: "Test".to_string(),
+        symbol: "TEST".to_string(),
+        decimals: 6,
+        initial_balances: vec![],
+        mint: Some(MinterResponse {
+            minter: governance.clone().into_string(),
+            cap: None,
+        }),
+        marketing: None,
+        ..Default::default()
+    };
+
+    let info = mock_info(governance.as_str(), &[]);
+    let _res = instantiate(deps.as_mut().into_empty(), mock_env(), info.clone(), msg).unwrap();
+
+    let _res = execute(
+        deps.as_mut().into_empty(),
+        mock_env(),
+        info.clone(),
+        crate::msg::ExecuteMsg::ProposeMinter { new_minter: "user".to_string(), expires: None }
+    ).unwrap();
+
+    let _res = execute(
+        deps.as_mut().into_empty(),
+        mock_env(),
+        info.clone(),
+        crate::msg::ExecuteMsg::UpdateMinter { new_minter: None }
+    ).unwrap();
+
+    // renouncing also drops the pending proposal
+    let err = execute(
+        deps.as_mut().into_empty(),
+        mock_env(),
+        mock_info("user", &[]),
+        crate::msg::ExecuteMsg::AcceptMinter {}
+    ).unwrap_err();
+    assert_eq!(err, ContractError::NoPendingMinter {});
+
+    let err = execute(
+        deps.as_mut().into_empty(),
+        mock_env(),
+        info,
+        crate::msg::ExecuteMsg::Mint { recipient: "user".to_string(), amount: Uint128::from(1u128) }
+    ).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+#[test]
+fn migrate_reports_versions() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        name: "Test".to_string(),
+        symbol: "TEST".to_string(),
+        decimals: 6,
+        initial_balances: vec![],
+        mint: None,
+        marketing: None,
+        ..Default::default()
+    };
+
+    let info = mock_info("creator", &[]);
+    let _res = instantiate(deps.as_mut().into_empty(), mock_env(), info, msg).unwrap();
+
+    let res = migrate(deps.as_mut().into_empty(), mock_env(), MigrateMsg { batch_limit: None, owner: None }).unwrap();
+    assert_eq!(res.attributes[0], ("action", "migrate"));
+    assert_eq!(res.attributes[1], ("from_version", env!("CARGO_PKG_VERSION")));
+    assert_eq!(res.attributes[2], ("to_version", env!("CARGO_PKG_VERSION")));
+
+    let version = cw2::get_contract_version(deps.as_ref().storage).unwrap();
+    assert_eq!(version.contract, "crates.io:terraport-token");
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+}
+
+#[test]
+fn migrate_rejects_other_contract() {
+    let mut deps = mock_dependencies(&[]);
+
+    cw2::set_contract_version(deps.as_mut().storage, "crates.io:cw20-base", "0.0.1").unwrap();
+
+    let err = migrate(deps.as_mut().into_empty(), mock_env(), MigrateMsg { batch_limit: None, owner: None }).unwrap_err();
+    assert_eq!(err, ContractError::CannotMigrate {
+        previous_contract: "crates.io:cw20-base".to_string(),
+    });
+}
+
+#[test]
+fn migrate_sets_owner() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        name: "Test".to_string(),
+        symbol: "TEST".to_string(),
+        decimals: 6,
+        initial_balances: vec![],
+        mint: None,
+        marketing: None,
+        ..Default::default()
+    };
+
+    let info = mock_info("creator", &[]);
+    let _res = instantiate(deps.as_mut().into_empty(), mock_env(), info, msg).unwrap();
+
+    let owner = |deps: cosmwasm_std::Deps| {
+        let res = query(deps, mock_env(), QueryMsg::Ownership {}).unwrap();
+        from_binary::<crate::msg::OwnershipResponse>(&res).unwrap().owner
+    };
+    assert_eq!(owner(deps.as_ref().into_empty()), None);
+
+    let msg = MigrateMsg { batch_limit: None, owner: Some("owner".to_string()) };
+    let _res = migrate(deps.as_mut().into_empty(), mock_env(), msg).unwrap();
+    assert_eq!(owner(deps.as_ref().into_empty()), Some("owner".to_string()));
+}
+
+#[test]
+fn migrate_rejects_downgrade() {
+    let mut deps = mock_dependencies(&[]);
+
+    cw2::set_contract_version(deps.as_mut().storage, "crates.io:terraport-token", "99.0.0").unwrap();
+
+    let err = migrate(deps.as_mut().into_empty(), mock_env(), MigrateMsg { batch_limit: None, owner: None }).unwrap_err();
+    assert_eq!(err, ContractError::CannotMigrateVersion {
+        previous_version: "99.0.0".to_string(),
+    });
+
+    cw2::set_contract_version(deps.as_mut().storage, "crates.io:terraport-token", "latest").unwrap();
+
+    let err = migrate(deps.as_mut().into_empty(), mock_env(), MigrateMsg { batch_limit: None, owner: None }).unwrap_err();
+    assert!(matches!(err, ContractError::SemVer(_)));
+}
+
+#[test]
+fn migrate_backfills_spender_index_in_pages() {
+    use cosmwasm_std::Order;
+    use cw20::{ AllSpenderAllowancesResponse, AllowanceResponse, Expiration };
+    use cw_storage_plus::Map;
+    use crate::state::LEGACY_ALLOWANCES_SPENDER;
+
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        name: "Test".to_string(),
+        symbol: "TEST".to_string(),
+        decimals: 6,
+        initial_balances: vec![],
+        mint: None,
+        marketing: None,
+        ..Default::default()
+    };
+
+    let info = mock_info("creator", &[]);
+    let _res = instantiate(deps.as_mut().into_empty(), mock_env(), info, msg).unwrap();
+
+    // recreate the pre-0.1.0 layout: allowances without index entries plus the mirrored map
+    cw2::set_contract_version(deps.as_mut().storage, "crates.io:terraport-token", "0.0.0").unwrap();
+    let old_allowances: Map<(&Addr, &Addr), AllowanceResponse> = Map::new("allowance");
+    let spender = Addr::unchecked("spender");
+    let owners = ["owner1", "owner2", "owner3"].map(Addr::unchecked);
+    for owner in owners.iter() {
+        let allowance = AllowanceResponse {
+            allowance: Uint128::new(100),
+            expires: Expiration::Never {},
+        };
+        old_allowances.save(deps.as_mut().storage, (owner, &spender), &allowance).unwrap();
+        LEGACY_ALLOWANCES_SPENDER.save(
+            deps.as_mut().storage,
+            (&spender, owner),
+            &allowance
+        ).unwrap();
+    }
+
+    let spender_query = QueryMsg::AllSpenderAllowances {
+        spender: spender.to_string(),
+        start_after: None,
+        limit: None,
+        include_expired: None,
+    };
+
+    let res = migrate(
+        deps.as_mut().into_empty(),
+        mock_env(),
+        MigrateMsg { batch_limit: Some(2), owner: None }
+    ).unwrap();
+    assert_eq!(res.attributes[3], ("remaining_steps", "3"));
+
+    // the index is incomplete, so lookups by spender are refused
+    query(deps.as_ref().into_empty(), mock_env(), spender_query.clone()).unwrap_err();
+
+    let info = mock_info("anyone", &[]);
+    let mut remaining = "3".to_string();
+    while remaining != "0" {
+        let res = execute(
+            deps.as_mut().into_empty(),
+            mock_env(),
+            info.clone(),
+            crate::msg::ExecuteMsg::ContinueMigration { limit: Some(2) }
+        ).unwrap();
+        remaining = res.attributes[1].value.clone();
+    }
+
+    let query_res = query(deps.as_ref().into_empty(), mock_env(), spender_query).unwrap();
+    let allowances: AllSpenderAllowancesResponse = from_binary(&query_res).unwrap();
+    let found = allowances.allowances
+        .into_iter()
+        .map(|a| a.owner)
+        .collect::<Vec<_>>();
+    assert_eq!(found, vec!["owner1", "owner2", "owner3"]);
+
+    let legacy = LEGACY_ALLOWANCES_SPENDER.keys(
+        deps.as_ref().storage,
+        None,
+        None,
+        Order::Ascending
+    ).count();
+    assert_eq!(legacy, 0);
+}
+
+#[test]
+fn batch_transfer() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        name: "Test".to_string(),
+        symbol: "TEST".to_string(),
+        decimals: 6,
+        initial_balances: vec![cw20::Cw20Coin {
+            address: "governance".to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+        mint: None,
+        marketing: None,
+        ..Default::default()
+    };
+
+    let info = mock_info("governance", &[]);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut().into_empty(), mock_env(), info.clone(), msg).unwrap();
+
+    let err = execute(
+        deps.as_mut().into_empty(),
+        mock_env(),
+        info.clone(),
+        crate::msg::ExecuteMsg::BatchTransfer {
+            transfers: vec![
+                cw20::Cw20Coin { address: "user1".to_string(), amount: Uint128::from(100u128) },
+                cw20::Cw20Coin { address: "user1".to_string(), amount: Uint128::from(100u128) }
+            ],
+        }
+    ).unwrap_err();
+    assert_eq!(err, ContractError::DuplicateRecipients {});
+
     let res = execute(
         deps.as_mut().into_empty(),
         mock_env(),
         info,
-        crate::msg::ExecuteMsg::UpdateMinter { new_minter: Some("user".to_string()) }
+        crate::msg::ExecuteMsg::BatchTransfer {
+            transfers: vec![
+                cw20::Cw20Coin { address: "user1".to_string(), amount: Uint128::from(100u128) },
+                cw20::Cw20Coin { address: "user2".to_string(), amount: Uint128::from(300u128) }
+            ],
+        }
     ).unwrap();
+    assert_eq!(res.attributes[2], ("amount", "400"));
+    assert_eq!(res.attributes[3], ("to", "user1"));
+    assert_eq!(res.attributes[6], ("to", "user2"));
+
+    for (address, expected) in [("governance", 600u128), ("user1", 100u128), ("user2", 300u128)] {
+        let query_res = query(deps.as_ref().into_empty(), mock_env(), QueryMsg::Balance {
+            address: address.to_string(),
+        }).unwrap();
+        let balance_res: BalanceResponse = from_binary(&query_res).unwrap();
+        assert_eq!(BalanceResponse { balance: Uint128::from(expected) }, balance_res);
+    }
+}
+
+#[test]
+fn batch_send_with_insufficient_balance() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        name: "Test".to_string(),
+        symbol: "TEST".to_string(),
+        decimals: 6,
+        initial_balances: vec![cw20::Cw20Coin {
+            address: "governance".to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+        mint: None,
+        marketing: None,
+        ..Default::default()
+    };
+
+    let info = mock_info("governance", &[]);
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut().into_empty(), mock_env(), info.clone(), msg).unwrap();
+
+    let send = |contract: &str, amount: u128| crate::msg::BatchSendItem {
+        contract: contract.to_string(),
+        amount: Uint128::from(amount),
+        msg: Binary::default(),
+    };
+
+    let err = execute(
+        deps.as_mut().into_empty(),
+        mock_env(),
+        info.clone(),
+        crate::msg::ExecuteMsg::BatchSend {
+            sends: vec![send("contract1", 600), send("contract2", 600)],
+        }
+    ).unwrap_err();
+    assert!(matches!(err, ContractError::Std(StdError::Overflow { .. })));
+
+    let res = execute(
+        deps.as_mut().into_empty(),
+        mock_env(),
+        info,
+        crate::msg::ExecuteMsg::BatchSend {
+            sends: vec![send("contract1", 600), send("contract2", 400)],
+        }
+    ).unwrap();
+    assert_eq!(res.messages.len(), 2);
+}
+
+#[test]
+fn holder_count_at_height() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        name: "Test".to_string(),
+        symbol: "TEST".to_string(),
+        decimals: 6,
+        initial_balances: vec![
+            cw20::Cw20Coin { address: "holder1".to_string(), amount: Uint128::from(1000u128) },
+            cw20::Cw20Coin { address: "holder2".to_string(), amount: Uint128::zero() }
+        ],
+        mint: None,
+        marketing: None,
+        ..Default::default()
+    };
+
+    let mut env = mock_env();
+    let start = env.block.height;
+    let info = mock_info("creator", &[]);
+    let _res = instantiate(deps.as_mut().into_empty(), env.clone(), info, msg).unwrap();
+
+    env.block.height += 1;
+    let _res = execute(
+        deps.as_mut().into_empty(),
+        env.clone(),
+        mock_info("holder1", &[]),
+        crate::msg::ExecuteMsg::Transfer {
+            recipient: "holder2".to_string(),
+            amount: Uint128::from(400u128),
+        }
+    ).unwrap();
+
+    env.block.height += 1;
+    let _res = execute(
+        deps.as_mut().into_empty(),
+        env.clone(),
+        mock_info("holder1", &[]),
+        crate::msg::ExecuteMsg::Burn { amount: Uint128::from(600u128) }
+    ).unwrap();
+
+    let count_at = |height: u64| {
+        let res = query(deps.as_ref().into_empty(), env.clone(), QueryMsg::HolderCountAt {
+            height,
+        }).unwrap();
+        from_binary::<crate::msg::HolderCountResponse>(&res).unwrap().count
+    };
+    assert_eq!(count_at(start + 1), 1);
+    assert_eq!(count_at(start + 2), 2);
+    assert_eq!(count_at(start + 3), 1);
+}
+
+#[test]
+fn migrate_backfills_holder_count() {
+    use cosmwasm_std::Order;
+    use crate::state::{ BALANCES, HOLDER_COUNT };
+
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        name: "Test".to_string(),
+        symbol: "TEST".to_string(),
+        decimals: 6,
+        initial_balances: ["holder1", "holder2", "holder3"]
+            .iter()
+            .map(|address| cw20::Cw20Coin {
+                address: address.to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .collect(),
+        mint: None,
+        marketing: None,
+        ..Default::default()
+    };
+
+    let mut env = mock_env();
+    let info = mock_info("creator", &[]);
+    let _res = instantiate(deps.as_mut().into_empty(), env.clone(), info, msg).unwrap();
+
+    // recreate the 0.1.0 layout, which had no holder count
+    cw2::set_contract_version(deps.as_mut().storage, "crates.io:terraport-token", "0.1.0").unwrap();
+    HOLDER_COUNT.remove(deps.as_mut().storage, env.block.height).unwrap();
+    assert_eq!(BALANCES.keys(deps.as_ref().storage, None, None, Order::Ascending).count(), 3);
+
+    env.block.height += 1;
+    let res = migrate(
+        deps.as_mut().into_empty(),
+        env.clone(),
+        MigrateMsg { batch_limit: Some(2), owner: None }
+    ).unwrap();
+    assert_eq!(res.attributes[3], ("remaining_steps", "1"));
+
+    let count_query = QueryMsg::HolderCountAt { height: env.block.height + 1 };
+    query(deps.as_ref().into_empty(), env.clone(), count_query.clone()).unwrap_err();
+
+    // holder1 was already counted and leaves, holder4 is counted by the next page
+    let _res = execute(
+        deps.as_mut().into_empty(),
+        env.clone(),
+        mock_info("holder1", &[]),
+        crate::msg::ExecuteMsg::Transfer {
+            recipient: "holder4".to_string(),
+            amount: Uint128::from(100u128),
+        }
+    ).unwrap();
+
+    let _res = execute(
+        deps.as_mut().into_empty(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        crate::msg::ExecuteMsg::ContinueMigration { limit: None }
+    ).unwrap();
+
+    let res = query(deps.as_ref().into_empty(), env, count_query).unwrap();
+    let count: crate::msg::HolderCountResponse = from_binary(&res).unwrap();
+    assert_eq!(count.count, 3);
+}
+
+#[test]
+fn migrate_moves_supply_history() {
+    use crate::state::{ LEGACY_TOTAL_SUPPLY_HISTORY, SUPPLY_HISTORY };
+
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        name: "Test".to_string(),
+        symbol: "TEST".to_string(),
+        decimals: 6,
+        initial_balances: vec![],
+        mint: None,
+        marketing: None,
+        ..Default::default()
+    };
+
+    let mut env = mock_env();
+    let info = mock_info("creator", &[]);
+    let _res = instantiate(deps.as_mut().into_empty(), env.clone(), info, msg).unwrap();
+
+    // recreate the 0.2.0 layout, which only kept the total supply per height
+    cw2::set_contract_version(deps.as_mut().storage, "crates.io:terraport-token", "0.2.0").unwrap();
+    let start = env.block.height;
+    for (height, total_supply) in [(start, 100u128), (start + 1, 300), (start + 2, 250)] {
+        LEGACY_TOTAL_SUPPLY_HISTORY.save(
+            deps.as_mut().storage,
+            height,
+            &Uint128::new(total_supply)
+        ).unwrap();
+    }
+
+    env.block.height += 10;
+    let res = migrate(
+        deps.as_mut().into_empty(),
+        env.clone(),
+        MigrateMsg { batch_limit: Some(1), owner: None }
+    ).unwrap();
+    assert_eq!(res.attributes[3], ("remaining_steps", "1"));
+
+    // lookups fall back to the legacy history until it has been moved over
+    let supply_query = QueryMsg::TotalSupplyAt { block: start + 1 };
+    let res = query(deps.as_ref().into_empty(), env.clone(), supply_query.clone()).unwrap();
+    assert_eq!(from_binary::<Uint128>(&res).unwrap(), Uint128::new(300));
+    // the range query cannot fall back, so it is refused until then
+    let history_query = QueryMsg::SupplyHistory {
+        start_after: None,
+        end_before: None,
+        limit: None,
+        order: None,
+    };
+    query(deps.as_ref().into_empty(), env.clone(), history_query.clone()).unwrap_err();
+
+    let _res = execute(
+        deps.as_mut().into_empty(),
+        env.clone(),
+        mock_info("anyone", &[]),
+        crate::msg::ExecuteMsg::ContinueMigration { limit: None }
+    ).unwrap();
+    assert!(LEGACY_TOTAL_SUPPLY_HISTORY.is_empty(deps.as_ref().storage));
+
+    let res = query(deps.as_ref().into_empty(), env.clone(), supply_query).unwrap();
+    assert_eq!(from_binary::<Uint128>(&res).unwrap(), Uint128::new(300));
+    let record = SUPPLY_HISTORY.records().load(deps.as_ref().storage, start + 2).unwrap();
+    assert_eq!((record.minted, record.burned), (Uint128::zero(), Uint128::new(50)));
+
+    let res = query(deps.as_ref().into_empty(), env, history_query).unwrap();
+    let history: crate::msg::SupplyHistoryResponse = from_binary(&res).unwrap();
+    assert_eq!(history.entries.len(), 3);
+    assert_eq!(history.entries[1].minted, Uint128::new(200));
+}
+
+#[test]
+fn supply_history_backfill_keeps_records_of_the_migrate_block() {
+    use crate::state::{ LEGACY_TOTAL_SUPPLY_HISTORY, SUPPLY_HISTORY, TOKEN_INFO };
+
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        name: "Test".to_string(),
+        symbol: "TEST".to_string(),
+        decimals: 6,
+        initial_balances: vec![],
+        mint: Some(MinterResponse { minter: "minter".to_string(), cap: None }),
+        marketing: None,
+        ..Default::default()
+    };
+
+    let mut env = mock_env();
+    let info = mock_info("creator", &[]);
+    let _res = instantiate(deps.as_mut().into_empty(), env.clone(), info, msg).unwrap();
+
+    // recreate the 0.2.0 layout, with the last legacy entry in the block of the migration
+    cw2::set_contract_version(deps.as_mut().storage, "crates.io:terraport-token", "0.2.0").unwrap();
+    let start = env.block.height;
+    for (height, total_supply) in [(start, 100u128), (start + 1, 300)] {
+        LEGACY_TOTAL_SUPPLY_HISTORY.save(
+            deps.as_mut().storage,
+            height,
+            &Uint128::new(total_supply)
+        ).unwrap();
+    }
+    let mut token_info = TOKEN_INFO.load(deps.as_ref().storage).unwrap();
+    token_info.total_supply = Uint128::new(300);
+    TOKEN_INFO.save(deps.as_mut().storage, &token_info).unwrap();
+
+    env.block.height += 1;
+    let _res = migrate(
+        deps.as_mut().into_empty(),
+        env.clone(),
+        MigrateMsg { batch_limit: Some(1), owner: None }
+    ).unwrap();
+
+    // a mint in the same block records its own change before the backfill gets there
+    let _res = execute(
+        deps.as_mut().into_empty(),
+        env.clone(),
+        mock_info("minter", &[]),
+        crate::msg::ExecuteMsg::Mint { recipient: "user".to_string(), amount: Uint128::new(50) }
+    ).unwrap();
+    let _res = execute(
+        deps.as_mut().into_empty(),
+        env,
+        mock_info("anyone", &[]),
+        crate::msg::ExecuteMsg::ContinueMigration { limit: None }
+    ).unwrap();
+
+    let record = SUPPLY_HISTORY.records().load(deps.as_ref().storage, start + 1).unwrap();
+    assert_eq!(record.total_supply, Uint128::new(350));
+    assert_eq!((record.minted, record.burned), (Uint128::new(250), Uint128::zero()));
 }
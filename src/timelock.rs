@@ -0,0 +1,356 @@
+use cosmwasm_std::{
+    attr,
+    Addr,
+    Deps,
+    DepsMut,
+    Env,
+    MessageInfo,
+    Response,
+    StdResult,
+    Storage,
+};
+use cw_utils::Duration;
+
+use crate::compliance::execute_update_compliance_admin;
+use crate::contract::{ execute_update_marketing, execute_update_minter };
+use crate::error::ContractError;
+use crate::minters::{
+    assert_token_minter,
+    execute_propose_minter,
+    execute_set_minter,
+    execute_update_mint_cap,
+};
+use crate::mint_proposals::execute_update_mint_approval;
+use crate::msg::TimelockResponse;
+use crate::ownership::{
+    execute_accept_ownership,
+    execute_renounce_ownership,
+    execute_transfer_ownership,
+};
+use crate::pause::execute_update_pause_guardian;
+use crate::roles::{
+    assert_primary_holder,
+    assert_role,
+    execute_grant_role,
+    execute_set_role_admin,
+    has_role,
+    role_admin,
+};
+use crate::state::{
+    Role,
+    ScheduledOperation,
+    TimelockOperation,
+    NEXT_OPERATION_ID,
+    OPERATIONS,
+    OWNER,
+    TIMELOCK_DELAY,
+};
+
+/// Fails unless `sender` could make the change directly.
+fn assert_can_schedule(
+    storage: &dyn Storage,
+    operation: &TimelockOperation,
+    sender: &Addr
+) -> Result<(), ContractError> {
+    match operation {
+        TimelockOperation::ProposeMinter { .. } | TimelockOperation::SetMinter { .. } => {
+            assert_token_minter(storage, sender)
+        }
+        TimelockOperation::UpdateMintCap { .. } => {
+            if
+                !has_role(storage, Role::Minter, sender)? &&
+                !has_role(storage, Role::MintCapAdmin, sender)?
+            {
+                return Err(ContractError::Unauthorized {});
+            }
+            Ok(())
+        }
+        TimelockOperation::GrantRole { role, .. } | TimelockOperation::SetRoleAdmin { role, .. } => {
+            assert_role(storage, role_admin(storage, *role)?, sender)
+        }
+        TimelockOperation::UpdateTimelockDelay { .. } | TimelockOperation::UpdateMintApproval { .. } => {
+            assert_role(storage, Role::Admin, sender)
+        }
+        TimelockOperation::UpdateMinter { .. } => assert_primary_holder(storage, Role::Minter, sender),
+        TimelockOperation::UpdateMarketing { marketing: Some(_), .. } => {
+            assert_primary_holder(storage, Role::Marketing, sender)
+        }
+        TimelockOperation::UpdateMarketing { marketing: None, .. } => {
+            assert_role(storage, Role::Marketing, sender)
+        }
+        TimelockOperation::UpdatePauseGuardian { .. } => {
+            assert_primary_holder(storage, Role::Pauser, sender)
+        }
+        TimelockOperation::UpdateComplianceAdmin { .. } => {
+            assert_primary_holder(storage, Role::Compliance, sender)
+        }
+        TimelockOperation::TransferOwnership { .. } | TimelockOperation::RenounceOwnership {} => {
+            assert_primary_holder(storage, Role::Admin, sender)
+        }
+        TimelockOperation::AcceptOwnership {} => {
+            let ownership = OWNER.may_load(storage)?.unwrap_or_default();
+            if ownership.pending_owner.as_ref() != Some(sender) {
+                return Err(ContractError::Unauthorized {});
+            }
+            Ok(())
+        }
+    }
+}
+
+pub fn execute_update_timelock_delay(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    delay: Option<Duration>
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, Role::Admin, &info.sender)?;
+    match delay {
+        Some(delay) => TIMELOCK_DELAY.save(deps.storage, &delay)?,
+        None => TIMELOCK_DELAY.remove(deps.storage),
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "update_timelock_delay")
+        .add_attribute("delay", delay.map(|d| d.to_string()).unwrap_or_else(|| "None".to_string()));
+    Ok(res)
+}
+
+pub fn execute_schedule_operation(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    operation: TimelockOperation
+) -> Result<Response, ContractError> {
+    assert_can_schedule(deps.storage, &operation, &info.sender)?;
+    let delay = TIMELOCK_DELAY.may_load(deps.storage)?.unwrap_or(Duration::Height(0));
+    let eta = delay.after(&env.block);
+
+    let id = NEXT_OPERATION_ID.may_load(deps.storage)?.unwrap_or_default();
+    NEXT_OPERATION_ID.save(deps.storage, &(id + 1))?;
+    OPERATIONS.save(deps.storage, id, &ScheduledOperation {
+        operation,
+        proposer: info.sender.clone(),
+        eta,
+    })?;
+
+    let res = Response::new().add_attributes(
+        vec![
+            attr("action", "schedule_operation"),
+            attr("id", id.to_string()),
+            attr("proposer", info.sender),
+            attr("eta", eta.to_string())
+        ]
+    );
+    Ok(res)
+}
+
+pub fn execute_cancel_operation(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    id: u64
+) -> Result<Response, ContractError> {
+    let scheduled = OPERATIONS.may_load(deps.storage, id)?.ok_or(ContractError::UnknownOperation {})?;
+    if scheduled.proposer != info.sender && !has_role(deps.storage, Role::Admin, &info.sender)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    OPERATIONS.remove(deps.storage, id);
+
+    let res = Response::new().add_attributes(
+        vec![attr("action", "cancel_operation"), attr("id", id.to_string()), attr("by", info.sender)]
+    );
+    Ok(res)
+}
+
+pub fn execute_execute_operation(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    id: u64
+) -> Result<Response, ContractError> {
+    let scheduled = OPERATIONS.may_load(deps.storage, id)?.ok_or(ContractError::UnknownOperation {})?;
+    if !scheduled.eta.is_expired(&env.block) {
+        return Err(ContractError::OperationNotReady { eta: scheduled.eta.to_string() });
+    }
+    OPERATIONS.remove(deps.storage, id);
+
+    // the change runs with the proposer's privileges, which it must still hold
+    let info = MessageInfo { sender: scheduled.proposer, funds: vec![] };
+    let res = match scheduled.operation {
+        TimelockOperation::ProposeMinter { new_minter, expires } => {
+            execute_propose_minter(deps, env, info, new_minter, expires)
+        }
+        TimelockOperation::SetMinter { minter, cap, rate_limit, expires } => {
+            execute_set_minter(deps, env, info, minter, cap, rate_limit, expires)
+        }
+        TimelockOperation::UpdateMintCap { cap } => execute_update_mint_cap(deps, env, info, cap),
+        TimelockOperation::GrantRole { role, address } => {
+            execute_grant_role(deps, env, info, role, address)
+        }
+        TimelockOperation::SetRoleAdmin { role, admin_role } => {
+            execute_set_role_admin(deps, env, info, role, admin_role)
+        }
+        TimelockOperation::UpdateTimelockDelay { delay } => {
+            execute_update_timelock_delay(deps, env, info, delay)
+        }
+        TimelockOperation::UpdateMintApproval { config } => {
+            execute_update_mint_approval(deps, env, info, config)
+        }
+        TimelockOperation::UpdateMinter { new_minter } => {
+            execute_update_minter(deps, env, info, new_minter)
+        }
+        TimelockOperation::UpdateMarketing { project, description, marketing } => {
+            execute_update_marketing(deps, env, info, project, description, marketing)
+        }
+        TimelockOperation::UpdatePauseGuardian { guardian } => {
+            execute_update_pause_guardian(deps, env, info, guardian)
+        }
+        TimelockOperation::UpdateComplianceAdmin { admin } => {
+            execute_update_compliance_admin(deps, env, info, admin)
+        }
+        TimelockOperation::TransferOwnership { new_owner, expires } => {
+            execute_transfer_ownership(deps, env, info, new_owner, expires)
+        }
+        TimelockOperation::AcceptOwnership {} => execute_accept_ownership(deps, env, info),
+        TimelockOperation::RenounceOwnership {} => execute_renounce_ownership(deps, env, info),
+    }?;
+    Ok(res.add_attribute("operation_id", id.to_string()))
+}
+
+pub fn query_timelock(deps: Deps) -> StdResult<TimelockResponse> {
+    Ok(TimelockResponse { delay: TIMELOCK_DELAY.may_load(deps.storage)? })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::{ mock_dependencies, mock_env, mock_info };
+    use cosmwasm_std::Uint128;
+    use cw20::MinterResponse;
+
+    use crate::contract::{ execute, instantiate, query_minter };
+    use crate::enumerable::query_pending_operations;
+    use crate::msg::{ ExecuteMsg, InstantiateMsg };
+    use crate::roles::query_has_role;
+
+    fn do_instantiate(deps: DepsMut) {
+        let instantiate_msg = InstantiateMsg {
+            name: "Auto Gen".to_string(),
+            symbol: "AUTO".to_string(),
+            decimals: 3,
+            mint: Some(MinterResponse {
+                minter: "minter".into(),
+                cap: Some(Uint128::new(1000)),
+            }),
+            mint_cap_ceiling: Some(Uint128::new(5000)),
+            owner: Some("owner".into()),
+            timelock_delay: Some(Duration::Height(100)),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[]);
+        instantiate(deps, mock_env(), info, instantiate_msg).unwrap();
+    }
+
+    #[test]
+    fn privileged_changes_wait_for_the_delay() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+        let mut env = mock_env();
+        let minter = mock_info("minter", &[]);
+
+        let msg = ExecuteMsg::UpdateMintCap { cap: Uint128::new(2000) };
+        let err = execute(deps.as_mut(), env.clone(), minter.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::TimelockRequired {});
+
+        let operation = TimelockOperation::UpdateMintCap { cap: Uint128::new(2000) };
+        let msg = ExecuteMsg::ScheduleOperation { operation: operation.clone() };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg.clone());
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        execute(deps.as_mut(), env.clone(), minter, msg).unwrap();
+
+        let pending = query_pending_operations(deps.as_ref(), None, None).unwrap().operations;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].operation, operation);
+        assert_eq!(pending[0].proposer, "minter");
+
+        env.block.height += 99;
+        let msg = ExecuteMsg::ExecuteOperation { id: 0 };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg.clone());
+        assert_eq!(err.unwrap_err(), ContractError::OperationNotReady {
+            eta: format!("expiration height: {}", mock_env().block.height + 100),
+        });
+
+        env.block.height += 1;
+        execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), msg.clone()).unwrap();
+        assert_eq!(query_minter(deps.as_ref()).unwrap().unwrap().cap, Some(Uint128::new(2000)));
+        let err = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::UnknownOperation {});
+    }
+
+    #[test]
+    fn scheduled_operations_can_be_cancelled() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+        let mut env = mock_env();
+
+        let operation = TimelockOperation::GrantRole { role: Role::Minter, address: "bridge".into() };
+        let msg = ExecuteMsg::ScheduleOperation { operation };
+        execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+        let cancel = ExecuteMsg::CancelOperation { id: 0 };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("minter", &[]), cancel.clone());
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), cancel).unwrap();
+
+        env.block.height += 100;
+        let msg = ExecuteMsg::ExecuteOperation { id: 0 };
+        let err = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::UnknownOperation {});
+        assert!(!query_has_role(deps.as_ref(), Role::Minter, "bridge".into()).unwrap().has_role);
+        assert!(query_pending_operations(deps.as_ref(), None, None).unwrap().operations.is_empty());
+    }
+
+    #[test]
+    fn primary_holder_changes_wait_for_the_delay() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+        let mut env = mock_env();
+        let owner = mock_info("owner", &[]);
+
+        let direct = [
+            ("minter", ExecuteMsg::UpdateMinter { new_minter: None }),
+            ("owner", ExecuteMsg::UpdatePauseGuardian { guardian: Some("guardian".into()) }),
+            ("owner", ExecuteMsg::UpdateComplianceAdmin { admin: Some("officer".into()) }),
+            ("owner", ExecuteMsg::TransferOwnership { new_owner: "dao".into(), expires: None }),
+            ("dao", ExecuteMsg::AcceptOwnership {}),
+            ("owner", ExecuteMsg::RenounceOwnership {}),
+            ("marketing", ExecuteMsg::UpdateMarketing {
+                project: None,
+                description: None,
+                marketing: Some("agency".into()),
+            }),
+        ];
+        for (sender, msg) in direct {
+            let err = execute(deps.as_mut(), env.clone(), mock_info(sender, &[]), msg).unwrap_err();
+            assert_eq!(err, ContractError::TimelockRequired {});
+        }
+
+        // ownership moves once both the offer and its acceptance have waited out the delay
+        let operation = TimelockOperation::TransferOwnership { new_owner: "dao".into(), expires: None };
+        let msg = ExecuteMsg::ScheduleOperation { operation };
+        execute(deps.as_mut(), env.clone(), owner.clone(), msg).unwrap();
+        let msg = ExecuteMsg::ScheduleOperation { operation: TimelockOperation::AcceptOwnership {} };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("dao", &[]), msg.clone());
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+
+        env.block.height += 100;
+        let anyone = mock_info("anyone", &[]);
+        execute(deps.as_mut(), env.clone(), anyone.clone(), ExecuteMsg::ExecuteOperation { id: 0 }).unwrap();
+        execute(deps.as_mut(), env.clone(), mock_info("dao", &[]), msg).unwrap();
+        env.block.height += 100;
+        execute(deps.as_mut(), env, anyone, ExecuteMsg::ExecuteOperation { id: 1 }).unwrap();
+        assert!(query_has_role(deps.as_ref(), Role::Admin, "dao".into()).unwrap().has_role);
+        assert!(!query_has_role(deps.as_ref(), Role::Admin, "owner".into()).unwrap().has_role);
+    }
+}
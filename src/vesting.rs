@@ -0,0 +1,295 @@
+use cosmwasm_std::{
+    Addr,
+    BlockInfo,
+    Deps,
+    DepsMut,
+    Env,
+    MessageInfo,
+    Response,
+    StdResult,
+    Storage,
+    Uint128,
+};
+use cw20::BalanceResponse;
+
+use crate::contract::execute_mint;
+use crate::error::ContractError;
+use crate::msg::{ VestingAccount, VestingScheduleResponse };
+use crate::state::{ VestingSchedule, BALANCES, VESTING };
+
+impl VestingSchedule {
+    pub fn amount(&self) -> Uint128 {
+        match self {
+            VestingSchedule::Cliff { amount, .. } => *amount,
+            VestingSchedule::Linear { amount, .. } => *amount,
+            VestingSchedule::Step { amount, .. } => *amount,
+        }
+    }
+
+    /// How much of the amount has unlocked at `now`, in seconds
+    pub fn vested(&self, now: u64) -> Uint128 {
+        match self {
+            VestingSchedule::Cliff { amount, release_at } => {
+                if now >= *release_at { *amount } else { Uint128::zero() }
+            }
+            VestingSchedule::Linear { amount, start, end, cliff } => {
+                if now < cliff.unwrap_or(*start) || now <= *start {
+                    Uint128::zero()
+                } else if now >= *end {
+                    *amount
+                } else {
+                    amount.multiply_ratio(now - start, end - start)
+                }
+            }
+            VestingSchedule::Step { amount, start, interval, steps } => {
+                if now < *start {
+                    return Uint128::zero();
+                }
+                let completed = ((now - start) / interval).min(u64::from(*steps));
+                amount.multiply_ratio(completed, u64::from(*steps))
+            }
+        }
+    }
+
+    pub fn locked(&self, now: u64) -> Uint128 {
+        self.amount() - self.vested(now)
+    }
+
+    pub fn validate(&self) -> Result<(), ContractError> {
+        let valid = match self {
+            VestingSchedule::Cliff { .. } => true,
+            VestingSchedule::Linear { start, end, cliff, .. } =>
+                end > start && !matches!(cliff, Some(cliff) if cliff < start || cliff > end),
+            VestingSchedule::Step { interval, steps, .. } => *interval > 0 && *steps > 0,
+        };
+        if !valid || self.amount().is_zero() {
+            return Err(ContractError::InvalidVestingSchedule {});
+        }
+        Ok(())
+    }
+}
+
+pub fn locked_amount(storage: &dyn Storage, block: &BlockInfo, address: &Addr) -> StdResult<Uint128> {
+    let schedule = VESTING.may_load(storage, address)?;
+    Ok(schedule.map(|s| s.locked(block.time.seconds())).unwrap_or_default())
+}
+
+/// Locks part of the initial balances. Must run after those balances are created.
+pub fn create_vesting_accounts(
+    deps: &mut DepsMut,
+    accounts: &[VestingAccount]
+) -> Result<(), ContractError> {
+    for account in accounts {
+        let address = deps.api.addr_validate(&account.address)?;
+        account.schedule.validate()?;
+        if VESTING.has(deps.storage, &address) {
+            return Err(ContractError::VestingAlreadyExists {});
+        }
+        let balance = BALANCES.may_load(deps.storage, &address)?.unwrap_or_default();
+        if account.schedule.amount() > balance {
+            return Err(ContractError::InvalidVestingSchedule {});
+        }
+        VESTING.save(deps.storage, &address, &account.schedule)?;
+    }
+    Ok(())
+}
+
+pub fn execute_mint_vesting(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    schedule: VestingSchedule
+) -> Result<Response, ContractError> {
+    schedule.validate()?;
+    let rcpt_addr = deps.api.addr_validate(&recipient)?;
+    // a fully vested schedule no longer locks anything and can be replaced
+    if !locked_amount(deps.storage, &env.block, &rcpt_addr)?.is_zero() {
+        return Err(ContractError::VestingAlreadyExists {});
+    }
+
+    let amount = schedule.amount();
+    execute_mint(deps.branch(), env, info, recipient.clone(), amount)?;
+    VESTING.save(deps.storage, &rcpt_addr, &schedule)?;
+
+    let res = Response::new()
+        .add_attribute("action", "mint_vesting")
+        .add_attribute("to", recipient)
+        .add_attribute("amount", amount);
+    Ok(res)
+}
+
+pub fn query_vesting_schedule(
+    deps: Deps,
+    env: Env,
+    address: String
+) -> StdResult<VestingScheduleResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let schedule = VESTING.may_load(deps.storage, &address)?;
+    let locked = schedule
+        .as_ref()
+        .map(|s| s.locked(env.block.time.seconds()))
+        .unwrap_or_default();
+    Ok(VestingScheduleResponse { schedule, locked })
+}
+
+pub fn query_spendable_balance(deps: Deps, env: Env, address: String) -> StdResult<BalanceResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let balance = BALANCES.may_load(deps.storage, &address)?.unwrap_or_default();
+    let locked = locked_amount(deps.storage, &env.block, &address)?;
+    Ok(BalanceResponse { balance: balance.saturating_sub(locked) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::{ mock_dependencies, mock_env, mock_info };
+    use cosmwasm_std::Binary;
+    use cw20::{ Cw20Coin, MinterResponse };
+
+    use crate::contract::{ execute, instantiate, query_balance };
+    use crate::msg::{ ExecuteMsg, InstantiateMsg };
+
+    fn do_instantiate(deps: DepsMut, vesting: Vec<VestingAccount>) {
+        let instantiate_msg = InstantiateMsg {
+            name: "Auto Gen".to_string(),
+            symbol: "AUTO".to_string(),
+            decimals: 3,
+            initial_balances: vec![Cw20Coin {
+                address: "team".into(),
+                amount: Uint128::new(1000),
+            }],
+            mint: Some(MinterResponse {
+                minter: "minter".into(),
+                cap: None,
+            }),
+            initial_vesting: Some(vesting),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[]);
+        instantiate(deps, mock_env(), info, instantiate_msg).unwrap();
+    }
+
+    #[test]
+    fn schedules_unlock_over_time() {
+        let cliff = VestingSchedule::Cliff { amount: Uint128::new(100), release_at: 50 };
+        assert_eq!(cliff.locked(49), Uint128::new(100));
+        assert_eq!(cliff.locked(50), Uint128::zero());
+
+        let linear = VestingSchedule::Linear {
+            amount: Uint128::new(100),
+            start: 0,
+            end: 100,
+            cliff: Some(25),
+        };
+        assert_eq!(linear.vested(24), Uint128::zero());
+        assert_eq!(linear.vested(25), Uint128::new(25));
+        assert_eq!(linear.vested(150), Uint128::new(100));
+
+        let step = VestingSchedule::Step {
+            amount: Uint128::new(100),
+            start: 10,
+            interval: 10,
+            steps: 4,
+        };
+        assert_eq!(step.vested(19), Uint128::zero());
+        assert_eq!(step.vested(35), Uint128::new(50));
+        assert_eq!(step.vested(1000), Uint128::new(100));
+
+        let invalid = VestingSchedule::Linear {
+            amount: Uint128::new(100),
+            start: 10,
+            end: 10,
+            cliff: None,
+        };
+        assert_eq!(invalid.validate().unwrap_err(), ContractError::InvalidVestingSchedule {});
+    }
+
+    #[test]
+    fn locked_tokens_cannot_be_moved() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let now = env.block.time.seconds();
+        do_instantiate(deps.as_mut(), vec![VestingAccount {
+            address: "team".into(),
+            schedule: VestingSchedule::Linear {
+                amount: Uint128::new(800),
+                start: now,
+                end: now + 800,
+                cliff: None,
+            },
+        }]);
+
+        let spendable = query_spendable_balance(deps.as_ref(), env.clone(), "team".into()).unwrap();
+        assert_eq!(spendable.balance, Uint128::new(200));
+
+        let team = mock_info("team", &[]);
+        let msg = ExecuteMsg::Transfer { recipient: "other".into(), amount: Uint128::new(201) };
+        let err = execute(deps.as_mut(), env.clone(), team.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::VestingLocked {});
+
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: "spender".into(),
+            amount: Uint128::new(1000),
+            expires: None,
+        };
+        execute(deps.as_mut(), env.clone(), team.clone(), msg).unwrap();
+        let msg = ExecuteMsg::SendFrom {
+            owner: "team".into(),
+            contract: "contract".into(),
+            amount: Uint128::new(300),
+            msg: Binary::default(),
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("spender", &[]), msg.clone());
+        assert_eq!(err.unwrap_err(), ContractError::VestingLocked {});
+
+        // half way through, 400 more are unlocked
+        env.block.time = env.block.time.plus_seconds(400);
+        execute(deps.as_mut(), env.clone(), mock_info("spender", &[]), msg).unwrap();
+        let msg = ExecuteMsg::Transfer { recipient: "other".into(), amount: Uint128::new(300) };
+        execute(deps.as_mut(), env.clone(), team, msg).unwrap();
+        assert_eq!(query_balance(deps.as_ref(), "team".into()).unwrap().balance, Uint128::new(400));
+
+        let res = query_vesting_schedule(deps.as_ref(), env, "team".into()).unwrap();
+        assert_eq!(res.locked, Uint128::new(400));
+    }
+
+    #[test]
+    fn minter_creates_vesting() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut(), vec![]);
+
+        let env = mock_env();
+        let msg = ExecuteMsg::MintVesting {
+            recipient: "investor".into(),
+            schedule: VestingSchedule::Cliff {
+                amount: Uint128::new(500),
+                release_at: env.block.time.seconds() + 100,
+            },
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("team", &[]), msg.clone());
+        assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+        execute(deps.as_mut(), env.clone(), mock_info("minter", &[]), msg.clone()).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), mock_info("minter", &[]), msg.clone());
+        assert_eq!(err.unwrap_err(), ContractError::VestingAlreadyExists {});
+
+        assert_eq!(query_balance(deps.as_ref(), "investor".into()).unwrap().balance, Uint128::new(500));
+        let spendable = query_spendable_balance(deps.as_ref(), env.clone(), "investor".into()).unwrap();
+        assert_eq!(spendable.balance, Uint128::zero());
+
+        // once fully vested, the schedule can be replaced by a new one
+        let mut env = env;
+        env.block.time = env.block.time.plus_seconds(100);
+        let msg = ExecuteMsg::MintVesting {
+            recipient: "investor".into(),
+            schedule: VestingSchedule::Cliff {
+                amount: Uint128::new(200),
+                release_at: env.block.time.seconds() + 100,
+            },
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("minter", &[]), msg).unwrap();
+        let spendable = query_spendable_balance(deps.as_ref(), env, "investor".into()).unwrap();
+        assert_eq!(spendable.balance, Uint128::new(500));
+    }
+}
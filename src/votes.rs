@@ -0,0 +1,206 @@
+use cosmwasm_std::{ Addr, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage, Uint128 };
+
+use crate::error::ContractError;
+use crate::msg::{ DelegationResponse, VotingPowerResponse };
+use crate::state::{ BALANCES, DELEGATES, TOTAL_VOTING_POWER, VOTING_POWER };
+
+/// Moves `amount` votes from the delegatee of `from` to the delegatee of `to`. A None side,
+/// or an account that has not delegated, adds to or takes from the total voting power.
+pub fn move_voting_power(
+    storage: &mut dyn Storage,
+    height: u64,
+    from: Option<&Addr>,
+    to: Option<&Addr>,
+    amount: Uint128
+) -> StdResult<()> {
+    if amount.is_zero() || from == to {
+        return Ok(());
+    }
+
+    if let Some(delegatee) = from {
+        VOTING_POWER.update(storage, delegatee, height, |power| -> StdResult<_> {
+            Ok(power.unwrap_or_default().checked_sub(amount)?)
+        })?;
+    }
+    if let Some(delegatee) = to {
+        VOTING_POWER.update(storage, delegatee, height, |power| -> StdResult<_> {
+            Ok(power.unwrap_or_default() + amount)
+        })?;
+    }
+
+    match (from, to) {
+        (None, Some(_)) => {
+            TOTAL_VOTING_POWER.update(storage, height, |total| -> StdResult<_> {
+                Ok(total.unwrap_or_default() + amount)
+            })?;
+        }
+        (Some(_), None) => {
+            TOTAL_VOTING_POWER.update(storage, height, |total| -> StdResult<_> {
+                Ok(total.unwrap_or_default().checked_sub(amount)?)
+            })?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Keeps the votes of `address`'s delegatee in line with a balance change of `amount`.
+pub fn update_voting_power(
+    storage: &mut dyn Storage,
+    height: u64,
+    address: &Addr,
+    amount: Uint128,
+    increase: bool
+) -> StdResult<()> {
+    let delegatee = match DELEGATES.may_load(storage, address)? {
+        Some(delegatee) => delegatee,
+        None => {
+            return Ok(());
+        }
+    };
+    if increase {
+        move_voting_power(storage, height, None, Some(&delegatee), amount)
+    } else {
+        move_voting_power(storage, height, Some(&delegatee), None, amount)
+    }
+}
+
+pub fn execute_delegate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    delegatee: String
+) -> Result<Response, ContractError> {
+    let delegatee = deps.api.addr_validate(&delegatee)?;
+    let previous = DELEGATES.may_load(deps.storage, &info.sender)?;
+    let balance = BALANCES.may_load(deps.storage, &info.sender)?.unwrap_or_default();
+
+    move_voting_power(
+        deps.storage,
+        env.block.height,
+        previous.as_ref(),
+        Some(&delegatee),
+        balance
+    )?;
+    DELEGATES.save(deps.storage, &info.sender, &delegatee)?;
+
+    let res = Response::new()
+        .add_attribute("action", "delegate")
+        .add_attribute("delegator", info.sender)
+        .add_attribute(
+            "from_delegate",
+            previous.map(Into::into).unwrap_or_else(|| "None".to_string())
+        )
+        .add_attribute("to_delegate", delegatee)
+        .add_attribute("amount", balance);
+    Ok(res)
+}
+
+pub fn query_delegation(deps: Deps, address: String) -> StdResult<DelegationResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let delegatee = DELEGATES.may_load(deps.storage, &address)?;
+    Ok(DelegationResponse { delegatee: delegatee.map(Into::into) })
+}
+
+pub fn query_voting_power(deps: Deps, address: String) -> StdResult<VotingPowerResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let power = VOTING_POWER.may_load(deps.storage, &address)?.unwrap_or_default();
+    Ok(VotingPowerResponse { power })
+}
+
+pub fn query_voting_power_at(
+    deps: Deps,
+    address: String,
+    height: u64
+) -> StdResult<VotingPowerResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let power = VOTING_POWER.may_load_at_height(deps.storage, &address, height)?.unwrap_or_default();
+    Ok(VotingPowerResponse { power })
+}
+
+pub fn query_total_voting_power_at(deps: Deps, height: u64) -> StdResult<VotingPowerResponse> {
+    let power = TOTAL_VOTING_POWER.may_load_at_height(deps.storage, height)?.unwrap_or_default();
+    Ok(VotingPowerResponse { power })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::{ mock_dependencies, mock_env, mock_info };
+    use cw20::{ Cw20Coin, MinterResponse };
+
+    use crate::contract::{ execute, instantiate };
+    use crate::msg::{ ExecuteMsg, InstantiateMsg };
+
+    fn do_instantiate(deps: DepsMut) {
+        let instantiate_msg = InstantiateMsg {
+            name: "Auto Gen".to_string(),
+            symbol: "AUTO".to_string(),
+            decimals: 3,
+            initial_balances: vec![
+                Cw20Coin {
+                    address: "alice".into(),
+                    amount: Uint128::new(1000),
+                },
+                Cw20Coin {
+                    address: "pool".into(),
+                    amount: Uint128::new(500),
+                }
+            ],
+            mint: Some(MinterResponse {
+                minter: "minter".into(),
+                cap: None,
+            }),
+            ..Default::default()
+        };
+        let info = mock_info("creator", &[]);
+        instantiate(deps, mock_env(), info, instantiate_msg).unwrap();
+    }
+
+    fn power(deps: Deps, address: &str) -> Uint128 {
+        query_voting_power(deps, address.into()).unwrap().power
+    }
+
+    #[test]
+    fn delegated_votes_follow_balances() {
+        let mut deps = mock_dependencies();
+        do_instantiate(deps.as_mut());
+
+        let mut env = mock_env();
+        let start = env.block.height;
+        let delegate = |delegatee: &str| ExecuteMsg::Delegate { delegatee: delegatee.into() };
+        execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), delegate("alice")).unwrap();
+        execute(deps.as_mut(), env.clone(), mock_info("pool", &[]), delegate("bob")).unwrap();
+        assert_eq!(power(deps.as_ref(), "alice"), Uint128::new(1000));
+        assert_eq!(power(deps.as_ref(), "bob"), Uint128::new(500));
+
+        env.block.height += 1;
+        let msg = ExecuteMsg::Transfer { recipient: "pool".into(), amount: Uint128::new(300) };
+        execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), msg).unwrap();
+        let msg = ExecuteMsg::Mint { recipient: "carol".into(), amount: Uint128::new(200) };
+        execute(deps.as_mut(), env.clone(), mock_info("minter", &[]), msg).unwrap();
+        assert_eq!(power(deps.as_ref(), "alice"), Uint128::new(700));
+        assert_eq!(power(deps.as_ref(), "bob"), Uint128::new(800));
+
+        env.block.height += 1;
+        execute(deps.as_mut(), env.clone(), mock_info("alice", &[]), delegate("bob")).unwrap();
+        let msg = ExecuteMsg::Burn { amount: Uint128::new(100) };
+        execute(deps.as_mut(), env.clone(), mock_info("pool", &[]), msg).unwrap();
+        assert_eq!(power(deps.as_ref(), "alice"), Uint128::zero());
+        assert_eq!(power(deps.as_ref(), "bob"), Uint128::new(1400));
+
+        let at = |height: u64| {
+            query_voting_power_at(deps.as_ref(), "alice".into(), height).unwrap().power
+        };
+        assert_eq!(at(start + 1), Uint128::new(1000));
+        assert_eq!(at(start + 2), Uint128::new(700));
+        assert_eq!(at(start + 3), Uint128::zero());
+
+        // carol never delegated, so her tokens do not count
+        let total = |height: u64| query_total_voting_power_at(deps.as_ref(), height).unwrap().power;
+        assert_eq!(total(start + 2), Uint128::new(1500));
+        assert_eq!(total(start + 3), Uint128::new(1400));
+        assert_eq!(query_delegation(deps.as_ref(), "carol".into()).unwrap().delegatee, None);
+    }
+}